@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use wasm_bindgen::prelude::*;
 
 // WebAssembly用のコンソール出力設定
@@ -19,12 +21,542 @@ macro_rules! console_log {
     ($($t:tt)*) => {}
 }
 
+/// hdr_animeのデフォルトガンマ値
+const DEFAULT_HDR_GAMMA: f32 = 0.7;
+/// hdr_animeのデフォルト彩度倍率
+const DEFAULT_HDR_SATURATION: f32 = 1.8;
+/// hdr_animeのデフォルト色階調レベル数
+const DEFAULT_ANIME_LEVELS: u32 = 6;
+/// hdr_animeのデフォルトエッジ強調の強さ
+const DEFAULT_EDGE_STRENGTH: f32 = 0.5;
+/// Filter指定時のガウシアンブラー半径
+const DEFAULT_BLUR_RADIUS: f32 = 3.0;
+/// リトルエンディアンで解釈した画素のRGB部分のマスク
+const RGB_MASK: u32 = 0x00FF_FFFF;
+/// 画素単位の並列処理で1タスクが扱う画素数
+#[cfg(feature = "parallel")]
+const PARALLEL_CHUNK_PIXELS: usize = 4096;
+/// 透過表示用チェッカーボードの明るいマスの色
+const CHECKER_LIGHT: u8 = 204;
+/// 透過表示用チェッカーボードの暗いマスの色
+const CHECKER_DARK: u8 = 153;
+/// SSIMを計算するブロックの一辺のピクセル数
+const SSIM_BLOCK: usize = 8;
+/// ASCIIアート用の文字ランプ (暗い順)
+const ASCII_RAMP: &[u8] = b"@%#*+=-:. ";
+/// skin_smoothのバイラテラル半径
+const SKIN_SMOOTH_RADIUS: i32 = 2;
+/// skin_smoothのバイラテラル色差シグマ
+const SKIN_SMOOTH_SIGMA: f32 = 30.0;
+/// enhance_detailのアンシャープマスク量
+const ENHANCE_DETAIL_AMOUNT: f32 = 0.8;
+/// local_exposureの輝度マップの一辺の分割数
+const LOCAL_EXPOSURE_GRID: usize = 8;
+/// thermalのパレット (冷たい順: 青→シアン→緑→黄→赤)
+const THERMAL_COLORS: [[u8; 3]; 5] = [[0, 0, 255], [0, 255, 255], [0, 255, 0], [255, 255, 0], [255, 0, 0]];
+/// auto_enhanceで最後にかけるバイブランスの強さ
+const AUTO_ENHANCE_VIBRANCE: f32 = 0.3;
+/// spotlightで照らされた領域に掛ける明るさの倍率
+const SPOTLIGHT_GAIN: f32 = 1.25;
+/// spotlightで照らされていない領域に掛ける明るさの倍率
+const SPOTLIGHT_AMBIENT: f32 = 0.5;
+/// vibranceで肌色に適用する彩度強調の割合
+const VIBRANCE_SKIN_FACTOR: f32 = 0.3;
+/// blur_with_maskの最大ブラー半径の上限 (半径1ごとに全画面のブラーを行うため)
+const MAX_MASK_BLUR_RADIUS: f32 = 64.0;
+/// watercolorで隣接色をにじませる割合
+const WATERCOLOR_BLEED: f32 = 0.35;
+/// watercolorで輪郭を暗くする強さ
+const WATERCOLOR_EDGE_DARKEN: f32 = 0.6;
+/// dehazeでダークチャンネルを求める近傍の半径
+const DEHAZE_PATCH_RADIUS: usize = 7;
+/// dehazeの透過率の下限 (濃い霞の部分でノイズが増幅されすぎるのを防ぐ)
+const DEHAZE_MIN_TRANSMISSION: f32 = 0.1;
+
+/// 適用するフィルタの種類
+/// パラメータを持つフィルタはデフォルト値で適用される
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Filter {
+    /// HDRアニメ調
+    HdrAnime,
+    /// ガウシアンブラー (半径3.0)
+    GaussianBlur,
+    /// エッジ検出
+    EdgeDetection,
+    /// セピア調
+    SepiaTone,
+    /// ネガフィルム
+    Negative,
+    /// シャープネス
+    Sharpen,
+}
+
+/// グレースケール化・輝度計算に使う重み係数
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LumaWeights {
+    /// ITU-R BT.601 (SD映像、デフォルト): 0.299 / 0.587 / 0.114
+    Bt601,
+    /// ITU-R BT.709 (HD映像): 0.2126 / 0.7152 / 0.0722
+    Bt709,
+}
+
+/// ビネットの減光の形状
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VignetteShape {
+    /// 画像の縦横比に合わせた楕円
+    Elliptical,
+    /// 縁がぼけた矩形 (ソフトボックス)
+    Rectangular,
+}
+
+/// モザイク処理で各ブロックを塗りつぶす色の決め方
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelateMode {
+    /// ブロック内の平均色
+    Average,
+    /// ブロック内で最も多い色 (ベタ塗りの画像で色が混ざらない)
+    MostFrequent,
+}
+
+/// describe_filters・apply_opsで扱うフィルタのパラメータ定義
+struct ParamSpec {
+    name: &'static str,
+    default: f32,
+    min: f32,
+    max: f32,
+    /// 整数のパラメータかどうか
+    integer: bool,
+}
+
+/// apply_opsで指定できるフィルタの定義
+/// describe_filtersの一覧とapply_opsの適用処理はどちらもこの定義から作られる
+struct FilterSpec {
+    name: &'static str,
+    params: &'static [ParamSpec],
+    /// パラメータの値 (`params`の順、省略時はデフォルト値) を検証
+    validate: fn(&[f32]) -> Result<(), String>,
+    /// 検証済みのパラメータでフィルタを適用
+    apply: fn(&mut ImageProcessor, &mut [u8], &[f32]),
+}
+
+/// apply_opsで指定できるフィルタとパラメータの一覧
+/// 引数が数値のみで、画像の座標に依存しないフィルタを対象とする
+/// (色・座標・マスク・列挙型を引数に取るフィルタは含まない)
+const FILTER_CATALOG: &[FilterSpec] = &[
+    FilterSpec {
+        name: "hdr_anime",
+        params: &[
+            ParamSpec { name: "gamma", default: DEFAULT_HDR_GAMMA, min: 0.1, max: 3.0, integer: false },
+            ParamSpec { name: "saturation", default: DEFAULT_HDR_SATURATION, min: 0.0, max: 4.0, integer: false },
+            ParamSpec { name: "levels", default: DEFAULT_ANIME_LEVELS as f32, min: 2.0, max: 32.0, integer: true },
+            ParamSpec { name: "edge", default: DEFAULT_EDGE_STRENGTH, min: 0.0, max: 2.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.hdr_anime_tuned(data, v[0], v[1], v[2] as u32, v[3]),
+    },
+    FilterSpec {
+        name: "gaussian_blur",
+        params: &[
+            ParamSpec { name: "radius", default: DEFAULT_BLUR_RADIUS, min: 0.0, max: 50.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.gaussian_blur(data, v[0]),
+    },
+    FilterSpec {
+        name: "gaussian_blur_xy",
+        params: &[
+            ParamSpec { name: "radius_x", default: DEFAULT_BLUR_RADIUS, min: 0.0, max: 50.0, integer: false },
+            ParamSpec { name: "radius_y", default: DEFAULT_BLUR_RADIUS, min: 0.0, max: 50.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.gaussian_blur_xy(data, v[0], v[1]),
+    },
+    FilterSpec {
+        name: "gaussian_blur_sigma",
+        params: &[
+            ParamSpec { name: "sigma", default: 1.0, min: 0.0, max: 20.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.gaussian_blur_sigma(data, v[0]),
+    },
+    FilterSpec {
+        name: "gaussian_blur_linear",
+        params: &[
+            ParamSpec { name: "radius", default: DEFAULT_BLUR_RADIUS, min: 0.0, max: 50.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.gaussian_blur_linear(data, v[0]),
+    },
+    FilterSpec {
+        name: "gaussian_blur_reflect",
+        params: &[
+            ParamSpec { name: "radius", default: DEFAULT_BLUR_RADIUS, min: 0.0, max: 50.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.gaussian_blur_reflect(data, v[0]),
+    },
+    FilterSpec {
+        name: "gaussian_blur_premul",
+        params: &[
+            ParamSpec { name: "radius", default: DEFAULT_BLUR_RADIUS, min: 0.0, max: 50.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.gaussian_blur_premul(data, v[0]),
+    },
+    FilterSpec {
+        name: "edge_detection",
+        params: &[],
+        validate: |_| Ok(()),
+        apply: |p, data, _| p.edge_detection(data),
+    },
+    FilterSpec {
+        name: "edge_detection_denoised",
+        params: &[
+            ParamSpec { name: "noise_floor", default: 32.0, min: 0.0, max: 255.0, integer: true },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.edge_detection_denoised(data, v[0] as u8),
+    },
+    FilterSpec {
+        name: "sepia_tone",
+        params: &[],
+        validate: |_| Ok(()),
+        apply: |p, data, _| p.sepia_tone(data),
+    },
+    FilterSpec {
+        name: "sepia_hue",
+        params: &[
+            ParamSpec { name: "depth", default: 1.0, min: 0.0, max: 2.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.sepia_hue(data, v[0]),
+    },
+    FilterSpec {
+        name: "negative",
+        params: &[],
+        validate: |_| Ok(()),
+        apply: |p, data, _| p.negative(data),
+    },
+    FilterSpec {
+        name: "invert_luma",
+        params: &[],
+        validate: |_| Ok(()),
+        apply: |p, data, _| p.invert_luma(data),
+    },
+    FilterSpec {
+        name: "sharpen",
+        params: &[
+            ParamSpec { name: "amount", default: 1.0, min: 0.0, max: 5.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.sharpen_amount(data, v[0]),
+    },
+    FilterSpec {
+        name: "sharpen_luma",
+        params: &[
+            ParamSpec { name: "amount", default: 1.0, min: 0.0, max: 5.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.sharpen_luma(data, v[0]),
+    },
+    FilterSpec {
+        name: "high_pass",
+        params: &[
+            ParamSpec { name: "radius", default: DEFAULT_BLUR_RADIUS, min: 0.0, max: 50.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.high_pass(data, v[0]),
+    },
+    FilterSpec {
+        name: "edge_enhance",
+        params: &[
+            ParamSpec { name: "strength", default: DEFAULT_EDGE_STRENGTH, min: 0.0, max: 2.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.edge_enhance(data, v[0]),
+    },
+    FilterSpec {
+        name: "enhance_detail",
+        params: &[],
+        validate: |_| Ok(()),
+        apply: |p, data, _| p.enhance_detail(data),
+    },
+    FilterSpec {
+        name: "posterize",
+        params: &[
+            ParamSpec { name: "levels", default: DEFAULT_ANIME_LEVELS as f32, min: 2.0, max: 32.0, integer: true },
+        ],
+        validate: |v| validate_levels(v[0] as u32),
+        apply: |p, data, v| p.apply_anime_posterization(data, v[0] as u32),
+    },
+    FilterSpec {
+        name: "posterize_hsv",
+        params: &[
+            ParamSpec { name: "h_levels", default: 12.0, min: 0.0, max: 36.0, integer: true },
+            ParamSpec { name: "s_levels", default: 4.0, min: 0.0, max: 32.0, integer: true },
+            ParamSpec { name: "v_levels", default: 4.0, min: 0.0, max: 32.0, integer: true },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.posterize_hsv(data, v[0] as u32, v[1] as u32, v[2] as u32),
+    },
+    FilterSpec {
+        name: "dither_ordered",
+        params: &[
+            ParamSpec { name: "levels", default: 2.0, min: 2.0, max: 32.0, integer: true },
+            ParamSpec { name: "matrix_size", default: 4.0, min: 2.0, max: 8.0, integer: true },
+        ],
+        validate: |v| validate_dither(v[0] as u32, v[1] as u32),
+        apply: |p, data, v| p.apply_ordered_dither(data, v[0] as u32, v[1] as u32),
+    },
+    FilterSpec {
+        name: "comic",
+        params: &[
+            ParamSpec { name: "levels", default: DEFAULT_ANIME_LEVELS as f32, min: 2.0, max: 32.0, integer: true },
+            ParamSpec { name: "edge_threshold", default: 128.0, min: 0.0, max: 255.0, integer: true },
+        ],
+        validate: |v| validate_levels(v[0] as u32),
+        apply: |p, data, v| p.apply_comic(data, v[0] as u32, v[1] as u8),
+    },
+    FilterSpec {
+        name: "kuwahara",
+        params: &[
+            ParamSpec { name: "radius", default: 2.0, min: 0.0, max: 10.0, integer: true },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.kuwahara(data, v[0] as u32),
+    },
+    FilterSpec {
+        name: "watercolor",
+        params: &[
+            ParamSpec { name: "radius", default: 2.0, min: 0.0, max: 10.0, integer: true },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.watercolor(data, v[0] as u32),
+    },
+    FilterSpec {
+        name: "skin_smooth",
+        params: &[
+            ParamSpec { name: "strength", default: 0.5, min: 0.0, max: 1.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.skin_smooth(data, v[0]),
+    },
+    FilterSpec {
+        name: "despeckle",
+        params: &[
+            ParamSpec { name: "threshold", default: 40.0, min: 0.0, max: 255.0, integer: true },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.despeckle(data, v[0] as u8),
+    },
+    FilterSpec {
+        name: "anisotropic_diffusion",
+        params: &[
+            ParamSpec { name: "iterations", default: 10.0, min: 0.0, max: 50.0, integer: true },
+            ParamSpec { name: "kappa", default: 20.0, min: 1.0, max: 100.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.anisotropic_diffusion(data, v[0] as u32, v[1]),
+    },
+    FilterSpec {
+        name: "blur_by_luminance",
+        params: &[
+            ParamSpec { name: "radius", default: DEFAULT_BLUR_RADIUS, min: 0.0, max: 50.0, integer: false },
+            ParamSpec { name: "min_luma", default: 0.0, min: 0.0, max: 255.0, integer: true },
+            ParamSpec { name: "max_luma", default: 128.0, min: 0.0, max: 255.0, integer: true },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.blur_by_luminance(data, v[0], v[1] as u8, v[2] as u8),
+    },
+    FilterSpec {
+        name: "contrast_luma",
+        params: &[
+            ParamSpec { name: "factor", default: 1.2, min: 0.0, max: 3.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.contrast_luma(data, v[0]),
+    },
+    FilterSpec {
+        name: "vibrance",
+        params: &[
+            ParamSpec { name: "amount", default: 0.5, min: -1.0, max: 2.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.vibrance(data, v[0]),
+    },
+    FilterSpec {
+        name: "color_isolate",
+        params: &[
+            ParamSpec { name: "target_hue", default: 0.0, min: 0.0, max: 360.0, integer: false },
+            ParamSpec { name: "range", default: 30.0, min: 0.0, max: 180.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.color_isolate(data, v[0], v[1]),
+    },
+    FilterSpec {
+        name: "shift_hue_range",
+        params: &[
+            ParamSpec { name: "target_hue", default: 0.0, min: 0.0, max: 360.0, integer: false },
+            ParamSpec { name: "range", default: 30.0, min: 0.0, max: 180.0, integer: false },
+            ParamSpec { name: "shift", default: 120.0, min: -180.0, max: 180.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.shift_hue_range(data, v[0], v[1], v[2]),
+    },
+    FilterSpec {
+        name: "luma_key",
+        params: &[
+            ParamSpec { name: "low", default: 16.0, min: 0.0, max: 255.0, integer: true },
+            ParamSpec { name: "high", default: 240.0, min: 0.0, max: 255.0, integer: true },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.luma_key(data, v[0] as u8, v[1] as u8),
+    },
+    FilterSpec {
+        name: "adaptive_threshold",
+        params: &[
+            ParamSpec { name: "block_size", default: 15.0, min: 1.0, max: 101.0, integer: true },
+            ParamSpec { name: "c", default: 5.0, min: -64.0, max: 64.0, integer: true },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.adaptive_threshold(data, v[0] as u32, v[1] as i32),
+    },
+    FilterSpec {
+        name: "auto_levels_per_channel",
+        params: &[],
+        validate: |_| Ok(()),
+        apply: |p, data, _| p.auto_levels_per_channel(data),
+    },
+    FilterSpec {
+        name: "auto_white_balance",
+        params: &[],
+        validate: |_| Ok(()),
+        apply: |p, data, _| p.auto_white_balance(data),
+    },
+    FilterSpec {
+        name: "auto_enhance",
+        params: &[],
+        validate: |_| Ok(()),
+        apply: |p, data, _| p.auto_enhance(data),
+    },
+    FilterSpec {
+        name: "local_exposure",
+        params: &[
+            ParamSpec { name: "strength", default: 0.5, min: 0.0, max: 1.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.local_exposure(data, v[0]),
+    },
+    FilterSpec {
+        name: "dehaze",
+        params: &[
+            ParamSpec { name: "amount", default: 0.5, min: 0.0, max: 1.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.dehaze(data, v[0]),
+    },
+    FilterSpec {
+        name: "thermal",
+        params: &[],
+        validate: |_| Ok(()),
+        apply: |p, data, _| p.thermal(data),
+    },
+    FilterSpec {
+        name: "emboss_directional",
+        params: &[
+            ParamSpec { name: "angle_degrees", default: 135.0, min: 0.0, max: 360.0, integer: false },
+            ParamSpec { name: "depth", default: 1.0, min: 0.0, max: 5.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.emboss_directional(data, v[0], v[1]),
+    },
+    FilterSpec {
+        name: "polar_transform",
+        params: &[],
+        validate: |_| Ok(()),
+        apply: |p, data, _| p.polar_transform(data),
+    },
+    FilterSpec {
+        name: "kaleidoscope",
+        params: &[
+            ParamSpec { name: "segments", default: 6.0, min: 0.0, max: 24.0, integer: true },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.kaleidoscope(data, v[0] as u32),
+    },
+    FilterSpec {
+        name: "glitch",
+        params: &[
+            ParamSpec { name: "intensity", default: 0.5, min: 0.0, max: 1.0, integer: false },
+            ParamSpec { name: "seed", default: 1.0, min: 0.0, max: 65535.0, integer: true },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.glitch(data, v[0], v[1] as u32),
+    },
+    FilterSpec {
+        name: "circular_mask",
+        params: &[
+            ParamSpec { name: "feather", default: 0.0, min: 0.0, max: 100.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.circular_mask(data, v[0]),
+    },
+    FilterSpec {
+        name: "denoise_temporal",
+        params: &[
+            ParamSpec { name: "alpha", default: 0.5, min: 0.0, max: 1.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.denoise_temporal(data, v[0]),
+    },
+    FilterSpec {
+        name: "ghost_trail",
+        params: &[
+            ParamSpec { name: "decay", default: 0.8, min: 0.0, max: 1.0, integer: false },
+        ],
+        validate: |_| Ok(()),
+        apply: |p, data, v| p.ghost_trail(data, v[0]),
+    },
+];
+
+/// apply_opsで適用する検証済みの操作
+/// すべての操作を検証してから適用するため、途中でエラーになった場合も画像は変更されない
+struct Op {
+    spec: &'static FilterSpec,
+    /// パラメータの値 (`spec.params`の順)
+    values: Vec<f32>,
+}
+
+/// エッジ強調ステップで描画する輪郭線の設定
+#[derive(Clone, Copy)]
+struct EdgeOutline {
+    /// 輪郭線の色 [r, g, b]
+    color: [u8; 3],
+    /// 輪郭線の太さ (ピクセル、0で輪郭線を描かずにエッジを明るく強調する)
+    thickness: u32,
+    /// 輪郭線とするエッジ強度の閾値
+    threshold: u8,
+}
+
+impl EdgeOutline {
+    /// 輪郭線なし (従来のエッジ強調)
+    const NONE: EdgeOutline = EdgeOutline { color: [0, 0, 0], thickness: 0, threshold: 0 };
+}
+
 /// WebAssembly画像処理エンジン
 /// 高速な画像フィルタ処理を提供
 #[wasm_bindgen]
 pub struct ImageProcessor {
     width: u32,
     height: u32,
+    /// 時間方向の処理で保持する前フレーム (未保持の場合は空)
+    previous_frame: Vec<u8>,
+    /// 輝度計算に使う重み係数
+    luma_weights: LumaWeights,
 }
 
 #[wasm_bindgen]
@@ -37,14 +569,25 @@ impl ImageProcessor {
     #[wasm_bindgen(constructor)]
     pub fn new(width: u32, height: u32) -> ImageProcessor {
         console_log!("ImageProcessor initialized: {}x{}", width, height);
-        ImageProcessor { width, height }
+        ImageProcessor { width, height, previous_frame: Vec::new(), luma_weights: LumaWeights::Bt601 }
+    }
+
+    /// 輝度計算に使う重み係数を設定
+    /// グレースケールや輝度を参照するすべてのフィルタに適用される
+    /// 
+    /// # Arguments
+    /// * `weights` - 重み係数 (デフォルトはBT.601)
+    pub fn set_luma_weights(&mut self, weights: LumaWeights) {
+        self.luma_weights = weights;
     }
 
     /// ガウシアンブラーエフェクトを適用
     /// 
     /// # Arguments
     /// * `data` - RGBA画像データ (mutable)
-    /// * `radius` - ブラー半径
+    /// * `radius` - ブラー半径 (水平方向は画像の幅、垂直方向は高さが上限。
+    ///   それ以上の半径は画像全体の平均と同じ結果になるため打ち切られる)。
+    ///   累積和で区間の合計を求めるため、処理時間は半径によらない
     pub fn gaussian_blur(&mut self, data: &mut [u8], radius: f32) {
         console_log!("Applying gaussian blur with radius: {}", radius);
         
@@ -52,16 +595,60 @@ impl ImageProcessor {
             return;
         }
 
+        self.gaussian_blur_xy(data, radius, radius);
+    }
+
+    /// 水平方向と垂直方向で半径の異なるガウシアンブラーを適用
+    /// 一方向にだけ強くぼかす (流れるようなソフト効果など) 場合に使用
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `radius_x` - 水平方向のブラー半径 (0以下でその方向はぼかさない)
+    /// * `radius_y` - 垂直方向のブラー半径 (0以下でその方向はぼかさない)
+    pub fn gaussian_blur_xy(&mut self, data: &mut [u8], radius_x: f32, radius_y: f32) {
+        console_log!("Applying gaussian blur xy: radius_x={}, radius_y={}", radius_x, radius_y);
+        
         // 簡易ガウシアンブラー実装
         // 実際のプロダクションでは、より高度なアルゴリズムを使用
         let width = self.width as usize;
         let height = self.height as usize;
         
         // 水平方向のブラー
-        self.blur_horizontal(data, width, height, radius);
+        if radius_x > 0.0 {
+            self.blur_horizontal(data, width, height, radius_x);
+        }
         
         // 垂直方向のブラー
-        self.blur_vertical(data, width, height, radius);
+        if radius_y > 0.0 {
+            self.blur_vertical(data, width, height, radius_y);
+        }
+    }
+
+    /// Alphaチャンネルの平均化を選択してガウシアンブラーを適用
+    /// マスクのエッジ (クロマキーの境界など) を柔らかくする場合に使用
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `radius` - ブラー半径
+    /// * `include_alpha` - trueの場合はAlphaチャンネルもぼかす
+    pub fn gaussian_blur_with_alpha(&mut self, data: &mut [u8], radius: f32, include_alpha: bool) {
+        if !include_alpha {
+            self.gaussian_blur(data, radius);
+            return;
+        }
+        
+        console_log!("Applying gaussian blur (with alpha) with radius: {}", radius);
+        
+        if radius <= 0.0 {
+            return;
+        }
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        
+        // RGBAの4チャンネルを水平・垂直の順にブラー
+        self.blur_horizontal_by_row(data, width, height, 4, |_| radius);
+        self.blur_vertical_by_row(data, width, height, 4, |_| radius);
     }
 
     /// エッジ検出エフェクトを適用
@@ -71,68 +658,70 @@ impl ImageProcessor {
     pub fn edge_detection(&mut self, data: &mut [u8]) {
         console_log!("Applying edge detection");
         
-        let width = self.width as usize;
-        let height = self.height as usize;
-        let mut temp_data = data.to_vec();
+        self.apply_sobel_magnitude(data, 0);
+    }
 
-        // Sobelオペレータを使用したエッジ検出
-        for y in 1..height - 1 {
-            for x in 1..width - 1 {
-                let idx = (y * width + x) * 4;
-                
-                // グレースケール変換
-                let gray = self.get_gray_value(&temp_data, x, y, width);
-                
-                // Sobelフィルタ適用
-                let gx = self.sobel_x(&temp_data, x, y, width);
-                let gy = self.sobel_y(&temp_data, x, y, width);
-                
-                let magnitude = ((gx * gx + gy * gy) as f32).sqrt() as u8;
-                
-                data[idx] = magnitude;     // R
-                data[idx + 1] = magnitude; // G
-                data[idx + 2] = magnitude; // B
-                // Alpha値は保持
-            }
-        }
+    /// ノイズ除去付きのエッジ検出エフェクトを適用
+    /// 低照度時のセンサーノイズでエッジマップ全体が光るのを抑制する
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `noise_floor` - この値未満のSobel強度は0にする
+    pub fn edge_detection_denoised(&mut self, data: &mut [u8], noise_floor: u8) {
+        console_log!("Applying denoised edge detection with noise floor: {}", noise_floor);
+        
+        self.apply_sobel_magnitude(data, noise_floor);
     }
 
     /// セピア調エフェクトを適用
+    /// データ長が4の倍数でない場合は何もしない
     /// 
     /// # Arguments
     /// * `data` - RGBA画像データ (mutable)
     pub fn sepia_tone(&mut self, data: &mut [u8]) {
         console_log!("Applying sepia tone");
         
-        for i in (0..data.len()).step_by(4) {
-            let r = data[i] as f32;
-            let g = data[i + 1] as f32;
-            let b = data[i + 2] as f32;
-
-            // セピア変換行列
-            let new_r = (r * 0.393 + g * 0.769 + b * 0.189).min(255.0) as u8;
-            let new_g = (r * 0.349 + g * 0.686 + b * 0.168).min(255.0) as u8;
-            let new_b = (r * 0.272 + g * 0.534 + b * 0.131).min(255.0) as u8;
+        if for_each_pixel_u32(data, sepia_pixel).is_err() {
+            console_log!("Skipping sepia tone: data length is not a multiple of 4");
+        }
+    }
 
-            data[i] = new_r;
-            data[i + 1] = new_g;
-            data[i + 2] = new_b;
+    /// グレースケール化してから暖色を乗せるセピア調エフェクト
+    /// 行列方式の `sepia_tone` より明るい色が飽和しにくい
+    /// データ長が4の倍数でない場合は何もしない
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `depth` - 色味の深さ (0.0で純粋なグレースケール、1.0で標準)
+    pub fn sepia_hue(&mut self, data: &mut [u8], depth: f32) {
+        // depth 1.0 のときにグレー値へ加える色味 (R, G, B)
+        const SEPIA_TINT: [f32; 3] = [40.0, 20.0, -20.0];
+        
+        console_log!("Applying sepia hue with depth: {}", depth);
+        
+        let result = for_each_pixel_u32(data, |pixel| {
+            let [r, g, b, a] = pixel.to_le_bytes();
+            let gray = self.luma(r as f32, g as f32, b as f32);
+            let tint = |c: usize| (gray + SEPIA_TINT[c] * depth).round().clamp(0.0, 255.0) as u8;
             // Alpha値は保持
+            u32::from_le_bytes([tint(0), tint(1), tint(2), a])
+        });
+        if result.is_err() {
+            console_log!("Skipping sepia hue: data length is not a multiple of 4");
         }
     }
 
     /// ネガフィルム効果を適用
+    /// データ長が4の倍数でない場合は何もしない
     /// 
     /// # Arguments
     /// * `data` - RGBA画像データ (mutable)
     pub fn negative(&mut self, data: &mut [u8]) {
         console_log!("Applying negative effect");
         
-        for i in (0..data.len()).step_by(4) {
-            data[i] = 255 - data[i];         // R
-            data[i + 1] = 255 - data[i + 1]; // G
-            data[i + 2] = 255 - data[i + 2]; // B
-            // Alpha値は保持
+        // RGBの3バイトをまとめて反転 (Alpha値は保持)
+        if for_each_pixel_u32(data, |pixel| pixel ^ RGB_MASK).is_err() {
+            console_log!("Skipping negative effect: data length is not a multiple of 4");
         }
     }
 
@@ -143,20 +732,33 @@ impl ImageProcessor {
     pub fn sharpen(&mut self, data: &mut [u8]) {
         console_log!("Applying sharpen effect");
         
+        self.sharpen_amount(data, 1.0);
+    }
+
+    /// 強さを指定してシャープネス強化を適用
+    /// カーネルは「恒等カーネル + amount * ラプラシアン」で構成する
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `amount` - シャープネスの強さ (0.0で変化なし、1.0でsharpenと同等)
+    pub fn sharpen_amount(&mut self, data: &mut [u8], amount: f32) {
         let width = self.width as usize;
         let height = self.height as usize;
-        let mut temp_data = data.to_vec();
+        let temp_data = data.to_vec();
 
         // シャープネスカーネル
         let kernel = [
-            0.0, -1.0, 0.0,
-            -1.0, 5.0, -1.0,
-            0.0, -1.0, 0.0
+            0.0, -amount, 0.0,
+            -amount, 1.0 + 4.0 * amount, -amount,
+            0.0, -amount, 0.0
         ];
 
-        for y in 1..height - 1 {
+        for_each_row(&mut data[..width * height * 4], width * 4, |y, row| {
+            if y == 0 || y + 1 >= height {
+                return;
+            }
             for x in 1..width - 1 {
-                let idx = (y * width + x) * 4;
+                let idx = x * 4;
                 
                 for c in 0..3 { // RGB各チャンネル
                     let mut sum = 0.0;
@@ -170,10 +772,10 @@ impl ImageProcessor {
                         }
                     }
                     
-                    data[idx + c] = sum.max(0.0).min(255.0) as u8;
+                    row[idx + c] = sum.clamp(0.0, 255.0) as u8;
                 }
             }
-        }
+        });
     }
 
     /// HDRアニメ調エフェクトを適用
@@ -182,343 +784,6935 @@ impl ImageProcessor {
     /// # Arguments
     /// * `data` - RGBA画像データ (mutable)
     pub fn hdr_anime(&mut self, data: &mut [u8]) {
-        console_log!("Applying HDR anime effect");
+        self.hdr_anime_with_outline(data, EdgeOutline::NONE);
+    }
+
+    /// パラメータ指定でHDRアニメ調エフェクトを適用
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `gamma` - ガンマ補正値 (1.0未満で暗部を持ち上げる)
+    /// * `saturation` - 彩度の倍率
+    /// * `levels` - 色階調のレベル数 (2未満は2として扱う)
+    /// * `edge` - エッジ強調の強さ
+    pub fn hdr_anime_tuned(&mut self, data: &mut [u8], gamma: f32, saturation: f32, levels: u32, edge: f32) {
+        self.apply_hdr_anime(data, gamma, saturation, levels, edge, EdgeOutline::NONE);
+    }
+
+    /// 輪郭線付きのHDRアニメ調（セル画風）エフェクトを適用
+    /// hdr_animeのエッジ強調ステップで、強いエッジを指定色・指定太さの輪郭線として描画する
+    /// (太さ0ではhdr_animeと同じ結果になる)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `outline` - 輪郭線の色 [r, g, b]
+    /// * `thickness` - 輪郭線の太さ (ピクセル、0で輪郭線なし)
+    /// * `threshold` - 輪郭線とするエッジ強度の閾値
+    /// 
+    /// # Errors
+    /// `outline`が3要素でない場合
+    pub fn hdr_anime_outlined(&mut self, data: &mut [u8], outline: &[u8], thickness: u32, threshold: u8) -> Result<(), JsError> {
+        self.try_hdr_anime_outlined(data, outline, thickness, threshold).map_err(|e| JsError::new(&e))
+    }
+
+    /// 白飛び・黒つぶれしている画素数をカウント
+    /// いずれかのチャンネルが0または255の画素をクリップとみなす
+    /// hdr_animeはコントラストを強調するため、適用前の露出確認に使用
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ
+    /// 
+    /// # Returns
+    /// クリップしている画素数
+    pub fn count_clipped(&self, data: &[u8]) -> u32 {
+        data.chunks_exact(4)
+            .filter(|pixel| pixel[..3].iter().any(|&v| v == 0 || v == 255))
+            .count() as u32
+    }
+
+    /// Kuwaharaフィルタを適用
+    /// 近傍を4つの象限に分割し、輝度の分散が最小の象限の平均色を採用する
+    /// エッジを保ちながら絵画調に平滑化する
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `radius` - 各象限の半径
+    pub fn kuwahara(&mut self, data: &mut [u8], radius: u32) {
+        console_log!("Applying kuwahara filter with radius: {}", radius);
+        
+        if radius == 0 {
+            return;
+        }
         
         let width = self.width as usize;
         let height = self.height as usize;
+        let r = radius as i32;
+        let temp_data = data.to_vec();
         
-        // ステップ1: HDR強調処理
-        self.apply_hdr_enhancement(data, width, height);
-        
-        // ステップ2: アニメ調処理（色の階調化）
-        self.apply_anime_posterization(data, width, height);
-        
-        // ステップ3: エッジ強調
-        self.apply_edge_enhancement(data, width, height);
-    }
-}
-
-impl ImageProcessor {
-    /// 水平方向のブラー処理
-    fn blur_horizontal(&self, data: &mut [u8], width: usize, height: usize, radius: f32) {
-        let kernel_size = (radius * 2.0) as usize + 1;
-        let mut temp_data = data.to_vec();
+        // 象限の方向 (左上, 右上, 左下, 右下)
+        let quadrants = [(-1, -1), (1, -1), (-1, 1), (1, 1)];
         
-        for y in 0..height {
+        for_each_row(&mut data[..width * height * 4], width * 4, |y, row| {
             for x in 0..width {
-                let mut r_sum = 0.0;
-                let mut g_sum = 0.0;
-                let mut b_sum = 0.0;
-                let mut count = 0.0;
-                
-                for kx in 0..kernel_size {
-                    let px = x as i32 + kx as i32 - radius as i32;
-                    if px >= 0 && px < width as i32 {
-                        let idx = (y * width + px as usize) * 4;
-                        r_sum += temp_data[idx] as f32;
-                        g_sum += temp_data[idx + 1] as f32;
-                        b_sum += temp_data[idx + 2] as f32;
-                        count += 1.0;
-                    }
-                }
+                let mut best_variance = f32::MAX;
+                let mut best_mean = [0.0f32; 3];
                 
-                let idx = (y * width + x) * 4;
-                data[idx] = (r_sum / count) as u8;
-                data[idx + 1] = (g_sum / count) as u8;
-                data[idx + 2] = (b_sum / count) as u8;
-            }
-        }
-    }
-    
-    /// 垂直方向のブラー処理
-    fn blur_vertical(&self, data: &mut [u8], width: usize, height: usize, radius: f32) {
-        let kernel_size = (radius * 2.0) as usize + 1;
-        let mut temp_data = data.to_vec();
-        
-        for y in 0..height {
-            for x in 0..width {
-                let mut r_sum = 0.0;
-                let mut g_sum = 0.0;
-                let mut b_sum = 0.0;
-                let mut count = 0.0;
-                
-                for ky in 0..kernel_size {
-                    let py = y as i32 + ky as i32 - radius as i32;
-                    if py >= 0 && py < height as i32 {
-                        let idx = (py as usize * width + x) * 4;
-                        r_sum += temp_data[idx] as f32;
-                        g_sum += temp_data[idx + 1] as f32;
-                        b_sum += temp_data[idx + 2] as f32;
-                        count += 1.0;
+                for &(dx, dy) in quadrants.iter() {
+                    let mut sum = [0.0f32; 3];
+                    let mut luma_sum = 0.0;
+                    let mut luma_sq_sum = 0.0;
+                    let mut count = 0.0;
+                    
+                    for ky in 0..=r {
+                        for kx in 0..=r {
+                            // 境界はクランプ
+                            let px = (x as i32 + kx * dx).clamp(0, width as i32 - 1) as usize;
+                            let py = (y as i32 + ky * dy).clamp(0, height as i32 - 1) as usize;
+                            let pidx = (py * width + px) * 4;
+                            
+                            let luma = self.get_gray_value(&temp_data, px, py, width) as f32;
+                            for c in 0..3 {
+                                sum[c] += temp_data[pidx + c] as f32;
+                            }
+                            luma_sum += luma;
+                            luma_sq_sum += luma * luma;
+                            count += 1.0;
+                        }
+                    }
+                    
+                    let luma_mean = luma_sum / count;
+                    let variance = luma_sq_sum / count - luma_mean * luma_mean;
+                    
+                    if variance < best_variance {
+                        best_variance = variance;
+                        best_mean = [sum[0] / count, sum[1] / count, sum[2] / count];
                     }
                 }
                 
-                let idx = (y * width + x) * 4;
-                data[idx] = (r_sum / count) as u8;
-                data[idx + 1] = (g_sum / count) as u8;
-                data[idx + 2] = (b_sum / count) as u8;
+                let idx = x * 4;
+                for c in 0..3 {
+                    row[idx + c] = best_mean[c].round().min(255.0) as u8;
+                }
+                // Alpha値は保持
             }
+        });
+    }
+
+    /// 指定したフィルタを適用
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `filter` - 適用するフィルタ
+    pub fn apply(&mut self, data: &mut [u8], filter: Filter) {
+        match filter {
+            Filter::HdrAnime => self.hdr_anime(data),
+            Filter::GaussianBlur => self.gaussian_blur(data, DEFAULT_BLUR_RADIUS),
+            Filter::EdgeDetection => self.edge_detection(data),
+            Filter::SepiaTone => self.sepia_tone(data),
+            Filter::Negative => self.negative(data),
+            Filter::Sharpen => self.sharpen(data),
         }
     }
-    
-    /// グレースケール値を取得
-    fn get_gray_value(&self, data: &[u8], x: usize, y: usize, width: usize) -> u8 {
-        let idx = (y * width + x) * 4;
-        let r = data[idx] as f32;
-        let g = data[idx + 1] as f32;
-        let b = data[idx + 2] as f32;
-        (r * 0.299 + g * 0.587 + b * 0.114) as u8
+
+    /// フィルタを適用し、処理時間を計測
+    /// WebAssemblyでは`timing`フィーチャー有効時にperformance.now()を使用する
+    /// (無効時は常に0.0を返す)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `filter` - 適用するフィルタ
+    /// 
+    /// # Returns
+    /// 処理時間 (ミリ秒)
+    pub fn apply_timed(&mut self, data: &mut [u8], filter: Filter) -> f64 {
+        let start = now_ms();
+        self.apply(data, filter);
+        (now_ms() - start).max(0.0)
     }
-    
-    /// Sobel X方向フィルタ
-    fn sobel_x(&self, data: &[u8], x: usize, y: usize, width: usize) -> i32 {
-        let kernel = [-1, 0, 1, -2, 0, 2, -1, 0, 1];
-        let mut sum = 0;
-        
-        for ky in 0..3 {
-            for kx in 0..3 {
-                let px = x + kx - 1;
-                let py = y + ky - 1;
-                let gray = self.get_gray_value(data, px, py, width);
-                sum += gray as i32 * kernel[ky * 3 + kx];
-            }
+
+    /// フィルタを`n`回繰り返し適用
+    /// 段階的なブラーや拡散などを、JS側から何度も呼び出さずに実行できる
+    /// (作業バッファは各回のフィルタ処理がそれぞれ確保する)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `filter` - 適用するフィルタ
+    /// * `n` - 適用回数 (0で変化なし)
+    pub fn apply_n(&mut self, data: &mut [u8], filter: Filter, n: u32) {
+        for _ in 0..n {
+            self.apply(data, filter);
         }
-        
-        sum
     }
-    
-    /// Sobel Y方向フィルタ
-    fn sobel_y(&self, data: &[u8], x: usize, y: usize, width: usize) -> i32 {
-        let kernel = [-1, -2, -1, 0, 0, 0, 1, 2, 1];
-        let mut sum = 0;
+
+    /// ティルトシフト（ミニチュア風）エフェクトを適用
+    /// 水平方向の帯をピントが合った状態で残し、上下に向かって徐々にぼかす
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `focus_y` - ピント帯の上端のY座標
+    /// * `focus_height` - ピント帯の高さ
+    /// * `max_radius` - 画像の上端・下端での最大ブラー半径
+    pub fn tilt_shift(&mut self, data: &mut [u8], focus_y: u32, focus_height: u32, max_radius: f32) {
+        console_log!("Applying tilt shift: focus_y={}, focus_height={}", focus_y, focus_height);
         
-        for ky in 0..3 {
-            for kx in 0..3 {
-                let px = x + kx - 1;
-                let py = y + ky - 1;
-                let gray = self.get_gray_value(data, px, py, width);
-                sum += gray as i32 * kernel[ky * 3 + kx];
-            }
+        if max_radius <= 0.0 {
+            return;
         }
         
-        sum
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let focus_top = (focus_y as usize).min(height);
+        let focus_bottom = (focus_y as usize + focus_height as usize).min(height);
+        
+        // ピント帯から画像端までの最大距離で半径を正規化
+        let ramp = focus_top.max(height - focus_bottom).max(1) as f32;
+        let radius_at = |y: usize| {
+            let distance = if y < focus_top {
+                focus_top - y
+            } else if y >= focus_bottom {
+                y + 1 - focus_bottom
+            } else {
+                0
+            };
+            max_radius * (distance as f32 / ramp).min(1.0)
+        };
+        
+        // 水平方向のブラー
+        self.blur_horizontal_by_row(data, width, height, 3, radius_at);
+        
+        // 垂直方向のブラー
+        self.blur_vertical_by_row(data, width, height, 3, radius_at);
     }
 
-    /// HDR強調処理を適用
-    fn apply_hdr_enhancement(&self, data: &mut [u8], width: usize, height: usize) {
-        for i in (0..data.len()).step_by(4) {
-            let r = data[i] as f32 / 255.0;
-            let g = data[i + 1] as f32 / 255.0;
-            let b = data[i + 2] as f32 / 255.0;
+    /// 単色の枠を付けた画像を生成
+    /// 出力サイズは (width + 2 * thickness) x (height + 2 * thickness) となる
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ
+    /// * `thickness` - 枠の太さ (ピクセル)
+    /// * `r`, `g`, `b`, `a` - 枠の色
+    /// 
+    /// # Returns
+    /// 枠付きのRGBA画像データ
+    /// 
+    /// # Errors
+    /// `data`の長さが`width * height * 4`と一致しない場合
+    pub fn add_border(&self, data: &[u8], thickness: u32, r: u8, g: u8, b: u8, a: u8) -> Result<Vec<u8>, JsError> {
+        self.try_add_border(data, thickness, [r, g, b, a]).map_err(|e| JsError::new(&e))
+    }
 
-            // ガンマ補正（コントラスト強化）
-            let gamma = 0.7;
-            let enhanced_r = r.powf(gamma);
-            let enhanced_g = g.powf(gamma);
-            let enhanced_b = b.powf(gamma);
+    /// add_borderの出力サイズを取得
+    /// 
+    /// # Arguments
+    /// * `thickness` - 枠の太さ (ピクセル)
+    /// 
+    /// # Returns
+    /// [幅, 高さ]
+    pub fn bordered_dimensions(&self, thickness: u32) -> Vec<u32> {
+        vec![self.width + 2 * thickness, self.height + 2 * thickness]
+    }
 
-            // 彩度向上
-            let saturation_boost = 1.8;
-            let gray = enhanced_r * 0.299 + enhanced_g * 0.587 + enhanced_b * 0.114;
+    /// 特定の色相のみを残すカラー抽出エフェクトを適用
+    /// `target_hue ± range` の範囲外の画素をグレースケールにする
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `target_hue` - 残す色相 (0.0..360.0)
+    /// * `range` - 許容する色相の幅 (度)
+    pub fn color_isolate(&mut self, data: &mut [u8], target_hue: f32, range: f32) {
+        console_log!("Applying color isolate: hue={}, range={}", target_hue, range);
+        
+        for_each_pixel(data, |pixel| {
+            let (hue, saturation, _) = rgb_to_hsv(pixel[0], pixel[1], pixel[2]);
             
-            let final_r = gray + (enhanced_r - gray) * saturation_boost;
-            let final_g = gray + (enhanced_g - gray) * saturation_boost;
-            let final_b = gray + (enhanced_b - gray) * saturation_boost;
+            // 無彩色または範囲外の色相はグレースケール化
+            if saturation == 0.0 || hue_distance(hue, target_hue) > range {
+                let gray = self.luma(pixel[0] as f32, pixel[1] as f32, pixel[2] as f32) as u8;
+                pixel[..3].fill(gray);
+            }
+            // Alpha値は保持
+        });
+    }
 
-            data[i] = (final_r * 255.0).max(0.0).min(255.0) as u8;
-            data[i + 1] = (final_g * 255.0).max(0.0).min(255.0) as u8;
-            data[i + 2] = (final_b * 255.0).max(0.0).min(255.0) as u8;
-        }
+    /// ポスタリゼーション（色の階調化）を適用
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `levels` - 各チャンネルの階調数 (2以上)
+    /// 
+    /// # Errors
+    /// `levels`が2未満の場合
+    pub fn posterize(&mut self, data: &mut [u8], levels: u32) -> Result<(), JsError> {
+        self.try_posterize(data, levels).map_err(|e| JsError::new(&e))
     }
 
-    /// アニメ調色階調化処理を適用
-    fn apply_anime_posterization(&self, data: &mut [u8], _width: usize, _height: usize) {
-        let levels = 6; // 色階調のレベル数
-        let step = 255.0 / (levels - 1) as f32;
+    /// カラーマトリクス変換を適用
+    /// セピア・グレースケール・チャンネルミキサーなどを一般化した変換
+    /// 
+    /// 行列は行優先で、3x4 (RGBのみ) または 4x5 (RGBA) を受け付ける
+    /// 各行の最後の要素は0..255スケールのオフセット
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `matrix` - 12要素 (3x4) または 20要素 (4x5) の変換行列
+    /// 
+    /// # Errors
+    /// 行列の要素数が12または20でない場合
+    pub fn color_matrix(&mut self, data: &mut [u8], matrix: &[f32]) -> Result<(), JsError> {
+        self.try_color_matrix(data, matrix).map_err(|e| JsError::new(&e))
+    }
 
-        for i in (0..data.len()).step_by(4) {
-            for c in 0..3 {
-                let value = data[i + c] as f32;
-                let level = (value / step).round();
-                data[i + c] = (level * step).min(255.0) as u8;
-            }
-        }
+    /// 分離可能な1Dカーネルで畳み込みを適用
+    /// 同じカーネルを水平方向、垂直方向の順に適用する (境界はクランプ)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `kernel_1d` - 奇数長の1Dカーネル
+    /// 
+    /// # Errors
+    /// カーネル長が奇数でない場合
+    pub fn convolve_separable(&mut self, data: &mut [u8], kernel_1d: &[f32]) -> Result<(), JsError> {
+        self.try_convolve_separable(data, kernel_1d).map_err(|e| JsError::new(&e))
     }
 
-    /// エッジ強調処理を適用
-    fn apply_edge_enhancement(&self, data: &mut [u8], width: usize, height: usize) {
-        let mut temp_data = data.to_vec();
+    /// 輝度のみでコントラストを調整
+    /// 輝度を中間値128を中心に伸縮し、RGBを同じ比率でスケーリングするため
+    /// チャンネルごとの調整に比べて色相・彩度のずれが少ない
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `factor` - コントラスト倍率 (1.0で変化なし)
+    pub fn contrast_luma(&mut self, data: &mut [u8], factor: f32) {
+        console_log!("Applying luma contrast with factor: {}", factor);
         
-        for y in 1..height - 1 {
-            for x in 1..width - 1 {
+        for i in (0..data.len()).step_by(4) {
+            let r = data[i] as f32;
+            let g = data[i + 1] as f32;
+            let b = data[i + 2] as f32;
+            
+            let luma = self.luma(r, g, b);
+            let new_luma = ((luma - 128.0) * factor + 128.0).clamp(0.0, 255.0);
+            
+            if luma <= 0.0 {
+                // 黒はスケーリングできないため輝度をそのまま設定
+                let v = new_luma as u8;
+                data[i] = v;
+                data[i + 1] = v;
+                data[i + 2] = v;
+            } else {
+                let scale = new_luma / luma;
+                data[i] = (r * scale).round().clamp(0.0, 255.0) as u8;
+                data[i + 1] = (g * scale).round().clamp(0.0, 255.0) as u8;
+                data[i + 2] = (b * scale).round().clamp(0.0, 255.0) as u8;
+            }
+            // Alpha値は保持
+        }
+    }
+
+    /// ASCIIアート文字列を生成
+    /// 画像を`cols`列に縮小し、各セルの輝度を文字ランプに対応させる
+    /// 行数は縦横比から計算する (文字の縦長さを考慮して半分にする)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ
+    /// * `cols` - 出力の列数 (画像の幅が上限)
+    /// 
+    /// # Returns
+    /// 改行区切りのASCIIアート
+    pub fn to_ascii(&self, data: &[u8], cols: u32) -> String {
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let cols = (cols as usize).min(width);
+        if cols == 0 || height == 0 {
+            return String::new();
+        }
+        
+        let cell_width = width as f32 / cols as f32;
+        let rows = ((height as f32 / cell_width) * 0.5).round().max(1.0) as usize;
+        let cell_height = height as f32 / rows as f32;
+        
+        let mut output = String::with_capacity((cols + 1) * rows);
+        for row in 0..rows {
+            let y0 = (row as f32 * cell_height) as usize;
+            let y1 = (((row + 1) as f32 * cell_height) as usize).clamp(y0 + 1, height);
+            
+            for col in 0..cols {
+                let x0 = (col as f32 * cell_width) as usize;
+                let x1 = (((col + 1) as f32 * cell_width) as usize).clamp(x0 + 1, width);
+                
+                // セル内の平均輝度
+                let mut sum = 0.0;
+                for y in y0..y1 {
+                    for x in x0..x1 {
+                        sum += self.get_gray_value(data, x, y, width) as f32;
+                    }
+                }
+                let luma = sum / ((y1 - y0) * (x1 - x0)) as f32;
+                
+                let index = (luma / 255.0 * (ASCII_RAMP.len() - 1) as f32).round() as usize;
+                output.push(ASCII_RAMP[index.min(ASCII_RAMP.len() - 1)] as char);
+            }
+            output.push('\n');
+        }
+        
+        output
+    }
+
+    /// 色相・彩度を保ったまま輝度のみを反転
+    /// 輝度と色差に分解し、輝度だけを反転して再合成する
+    /// (negativeのように色は反転しない)
+    /// データ長が4の倍数でない場合は何もしない
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    pub fn invert_luma(&mut self, data: &mut [u8]) {
+        console_log!("Applying luma inversion");
+        
+        let result = for_each_pixel_u32(data, |pixel| {
+            let [r, g, b, a] = pixel.to_le_bytes();
+            let (r, g, b) = (r as f32, g as f32, b as f32);
+            
+            let luma = self.luma(r, g, b);
+            let inverted = 255.0 - luma;
+            
+            // 色差成分はそのまま、輝度のみ置き換える
+            let replace = |c: f32| (inverted + (c - luma)).round().clamp(0.0, 255.0) as u8;
+            // Alpha値は保持
+            u32::from_le_bytes([replace(r), replace(g), replace(b), a])
+        });
+        if result.is_err() {
+            console_log!("Skipping luma inversion: data length is not a multiple of 4");
+        }
+    }
+
+    /// トーンカーブを適用
+    /// 制御点を線形補間して256要素のLUTを作成し、RGB各チャンネルに適用する
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `points` - (入力, 出力) の組を並べた制御点 [x0, y0, x1, y1, ...] (0.0..255.0)
+    /// 
+    /// # Errors
+    /// 制御点が2点未満、要素数が奇数、範囲外、または入力値が昇順でない場合
+    pub fn tone_curve(&mut self, data: &mut [u8], points: &[f32]) -> Result<(), JsError> {
+        self.try_tone_curve(data, points).map_err(|e| JsError::new(&e))
+    }
+
+    /// 美肌（スキンスムージング）エフェクトを適用
+    /// 肌色の色相・彩度範囲に入る画素のみにバイラテラル平滑化をかけ、
+    /// 目や背景などはシャープなまま残す
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `strength` - 平滑化の強さ (0.0..1.0)
+    pub fn skin_smooth(&mut self, data: &mut [u8], strength: f32) {
+        console_log!("Applying skin smooth with strength: {}", strength);
+        
+        let strength = strength.clamp(0.0, 1.0);
+        if strength == 0.0 {
+            return;
+        }
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let temp_data = data.to_vec();
+        
+        for y in 0..height {
+            for x in 0..width {
                 let idx = (y * width + x) * 4;
+                let (hue, saturation, value) = rgb_to_hsv(temp_data[idx], temp_data[idx + 1], temp_data[idx + 2]);
+                if !is_skin_tone(hue, saturation, value) {
+                    continue;
+                }
                 
-                // エッジ検出
-                let gx = self.sobel_x(&temp_data, x, y, width);
-                let gy = self.sobel_y(&temp_data, x, y, width);
-                let edge_strength = ((gx * gx + gy * gy) as f32).sqrt() / 255.0;
+                let smoothed = self.bilateral_at(&temp_data, x, y, width, height, SKIN_SMOOTH_RADIUS, SKIN_SMOOTH_SIGMA);
+                for c in 0..3 {
+                    let original = temp_data[idx + c] as f32;
+                    data[idx + c] = (original + (smoothed[c] - original) * strength).round().clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+    }
+
+    /// 指定チャンネルのみをグレースケール画像として抽出
+    /// 色かぶりのデバッグなどに使用する
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ
+    /// * `channel` - 抽出するチャンネル (0: R, 1: G, 2: B, 3: A)
+    /// 
+    /// # Returns
+    /// 抽出したチャンネルをRGBに展開した不透明なRGBA画像データ
+    /// 
+    /// # Errors
+    /// `channel`が0..4の範囲外の場合
+    pub fn extract_channel(&self, data: &[u8], channel: u32) -> Result<Vec<u8>, JsError> {
+        self.try_extract_channel(data, channel).map_err(|e| JsError::new(&e))
+    }
+
+    /// グラデーションマップを適用
+    /// 輝度を複数の色のグラデーションに対応付ける (サーモグラフィ風の疑似カラーなど)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `stops` - RGBの組を並べた色の配列 [r0, g0, b0, r1, g1, b1, ...]
+    ///   (輝度0..255に等間隔で配置される)
+    /// 
+    /// # Errors
+    /// 色が2つ未満、または要素数が3の倍数でない場合
+    pub fn gradient_map(&mut self, data: &mut [u8], stops: &[u8]) -> Result<(), JsError> {
+        self.try_gradient_map(data, stops).map_err(|e| JsError::new(&e))
+    }
+
+    /// 外れ値（スペックルノイズ）除去を適用
+    /// 3x3近傍の中央値との差が`threshold`を超える画素のみを中央値で置き換え、
+    /// それ以外の画素はそのまま残す (全画素に中央値フィルタをかけるより細部が残る)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `threshold` - 外れ値と判定する中央値との差
+    pub fn despeckle(&mut self, data: &mut [u8], threshold: u8) {
+        console_log!("Applying despeckle with threshold: {}", threshold);
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let temp_data = data.to_vec();
+        
+        for y in 0..height {
+            for x in 0..width {
+                let idx = (y * width + x) * 4;
+                let median = self.median3x3(&temp_data, x, y, width, height);
                 
-                // エッジ強調を元の色に適用
-                let enhancement = 1.0 + edge_strength * 0.5;
+                let is_outlier = (0..3)
+                    .any(|c| temp_data[idx + c].abs_diff(median[c]) > threshold);
+                if is_outlier {
+                    data[idx..idx + 3].copy_from_slice(&median);
+                }
+            }
+        }
+    }
+
+    /// 異方性拡散（Perona-Malik）による平滑化を適用
+    /// 勾配が小さい領域内は平滑化し、勾配の大きいエッジでは拡散を抑えるため、
+    /// 反復によってエッジを保ったままノイズを除去できる
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `iterations` - 反復回数
+    /// * `kappa` - エッジと判定する勾配の大きさ (小さいほどエッジを保持)
+    pub fn anisotropic_diffusion(&mut self, data: &mut [u8], iterations: u32, kappa: f32) {
+        console_log!("Applying anisotropic diffusion: iterations={}, kappa={}", iterations, kappa);
+        
+        if iterations == 0 || kappa <= 0.0 {
+            return;
+        }
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        // 安定条件を満たす更新係数 (4近傍)
+        let lambda = 0.25;
+        
+        // 反復中の丸め誤差を避けるため浮動小数点で計算
+        let mut current: Vec<f32> = data.iter().map(|&v| v as f32).collect();
+        let mut next = current.clone();
+        let conduction = |gradient: f32| (-(gradient / kappa) * (gradient / kappa)).exp();
+        
+        for _ in 0..iterations {
+            for y in 0..height {
+                for x in 0..width {
+                    let idx = (y * width + x) * 4;
+                    // 境界では自身を近傍とみなす (勾配0)
+                    let north = if y > 0 { idx - width * 4 } else { idx };
+                    let south = if y + 1 < height { idx + width * 4 } else { idx };
+                    let west = if x > 0 { idx - 4 } else { idx };
+                    let east = if x + 1 < width { idx + 4 } else { idx };
+                    
+                    for c in 0..3 {
+                        let center = current[idx + c];
+                        let flux: f32 = [north, south, west, east]
+                            .iter()
+                            .map(|&n| {
+                                let gradient = current[n + c] - center;
+                                conduction(gradient) * gradient
+                            })
+                            .sum();
+                        next[idx + c] = center + lambda * flux;
+                    }
+                }
+            }
+            std::mem::swap(&mut current, &mut next);
+        }
+        
+        for (i, value) in current.iter().enumerate() {
+            if i % 4 != 3 {
+                data[i] = value.round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+
+    /// 極座標変換（リトルプラネット）エフェクトを適用
+    /// 画像の下端が中心、上端が外周になるよう中心まわりの極座標に再配置する
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    pub fn polar_transform(&mut self, data: &mut [u8]) {
+        console_log!("Applying polar transform");
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let temp_data = data.to_vec();
+        
+        let cx = (width as f32 - 1.0) / 2.0;
+        let cy = (height as f32 - 1.0) / 2.0;
+        let max_radius = cx.min(cy).max(1.0);
+        
+        for y in 0..height {
+            for x in 0..width {
+                let dx = x as f32 - cx;
+                let dy = y as f32 - cy;
                 
+                // 角度を横方向、中心からの距離を縦方向にマッピング
+                let angle = dy.atan2(dx);
+                let radius = ((dx * dx + dy * dy).sqrt() / max_radius).min(1.0);
+                let src_x = (angle + std::f32::consts::PI) / (2.0 * std::f32::consts::PI) * (width as f32 - 1.0);
+                let src_y = (1.0 - radius) * (height as f32 - 1.0);
+                
+                let color = self.sample_bilinear(&temp_data, src_x, src_y, width, height);
+                let idx = (y * width + x) * 4;
+                for c in 0..4 {
+                    data[idx + c] = color[c].round().clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+    }
+
+    /// タイル分割でフィルタを適用
+    /// 画像を`tile`x`tile`のタイルに分割し、近傍を参照するフィルタ (ブラー・Sobelなど) が
+    /// タイルの継ぎ目でも正しく動作するよう、のりしろ (ハロー) 付きで処理する
+    /// 画像全体のコピーを作らないため、大きな静止画でのピークメモリを抑えられる
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `filter` - 適用するフィルタ
+    /// * `tile` - タイルの一辺のピクセル数 (0の場合は分割しない)
+    pub fn process_tiled(&mut self, data: &mut [u8], filter: Filter, tile: u32) {
+        console_log!("Processing tiled: filter={:?}, tile={}", filter, tile);
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let tile = tile as usize;
+        if tile == 0 || (tile >= width && tile >= height) {
+            self.apply(data, filter);
+            return;
+        }
+        
+        let halo = filter_halo(filter);
+        let row_len = width * 4;
+        // 前の帯で上書き済みの行のうち、次の帯のハローに必要な元画像の行
+        let mut saved_rows: Vec<u8> = Vec::new();
+        let mut saved_from = 0;
+        
+        for band_y in (0..height).step_by(tile) {
+            let band_h = tile.min(height - band_y);
+            let y0 = band_y.saturating_sub(halo);
+            let y1 = (band_y + band_h + halo).min(height);
+            let mut band_out = vec![0u8; band_h * row_len];
+            
+            // 元画像の行を取得 (上書き済みの行は保存しておいた行を使用)
+            let source_row = |data: &[u8], saved_rows: &[u8], y: usize| -> Vec<u8> {
+                if y < band_y {
+                    let offset = (y - saved_from) * row_len;
+                    saved_rows[offset..offset + row_len].to_vec()
+                } else {
+                    data[y * row_len..(y + 1) * row_len].to_vec()
+                }
+            };
+            
+            for band_x in (0..width).step_by(tile) {
+                let tile_w = tile.min(width - band_x);
+                let x0 = band_x.saturating_sub(halo);
+                let x1 = (band_x + tile_w + halo).min(width);
+                
+                // ハロー付きのタイルを切り出してフィルタを適用
+                let mut sub = Vec::with_capacity((x1 - x0) * (y1 - y0) * 4);
+                for y in y0..y1 {
+                    let row = source_row(data, &saved_rows, y);
+                    sub.extend_from_slice(&row[x0 * 4..x1 * 4]);
+                }
+                let mut sub_processor = self.sub_processor((x1 - x0) as u32, (y1 - y0) as u32);
+                sub_processor.apply(&mut sub, filter);
+                
+                // ハローを除いた内側を帯の出力にコピー
+                let sub_row_len = (x1 - x0) * 4;
+                for y in band_y..band_y + band_h {
+                    let src = (y - y0) * sub_row_len + (band_x - x0) * 4;
+                    let dst = (y - band_y) * row_len + band_x * 4;
+                    band_out[dst..dst + tile_w * 4].copy_from_slice(&sub[src..src + tile_w * 4]);
+                }
+            }
+            
+            // 次の帯のハローに必要な元画像の行を保存してから書き戻す
+            let next_y = band_y + band_h;
+            let keep_from = next_y.saturating_sub(halo);
+            let mut next_saved = Vec::with_capacity((next_y - keep_from) * row_len);
+            for y in keep_from..next_y {
+                next_saved.extend_from_slice(&source_row(data, &saved_rows, y));
+            }
+            saved_rows = next_saved;
+            saved_from = keep_from;
+            
+            data[band_y * row_len..next_y * row_len].copy_from_slice(&band_out);
+        }
+    }
+
+    /// JavaScriptの関数で画素ごとの変換を適用
+    /// 各画素について`f(r, g, b, a)`を呼び出し、返された配列 `[r, g, b, a]` で上書きする
+    /// (要素が欠けている場合は元の値を保持)
+    /// 
+    /// 画素ごとにJavaScriptとの境界を越えるため非常に低速 (720pで約92万回の呼び出し)
+    /// 再コンパイルせずにフィルタを試作する用途を想定している
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `f` - (r, g, b, a) を受け取り [r, g, b, a] を返す関数
+    /// 
+    /// # Errors
+    /// 関数が例外を投げた場合 (それ以降の画素は処理しない)
+    pub fn map_pixels(&mut self, data: &mut [u8], f: &js_sys::Function) -> Result<(), JsValue> {
+        self.try_map_pixels_with(data, |pixel| {
+            let args = js_sys::Array::of4(
+                &JsValue::from(pixel[0]),
+                &JsValue::from(pixel[1]),
+                &JsValue::from(pixel[2]),
+                &JsValue::from(pixel[3]),
+            );
+            let result = js_sys::Array::from(&f.apply(&JsValue::NULL, &args)?);
+            
+            let mut output = pixel;
+            for (c, value) in output.iter_mut().enumerate() {
+                if let Some(v) = result.get(c as u32).as_f64() {
+                    *value = v.round().clamp(0.0, 255.0) as u8;
+                }
+            }
+            Ok(output)
+        })
+    }
+
+    /// 光源方向を指定したエンボス（浮き彫り）エフェクトを適用
+    /// SobelのX・Yカーネルを光源方向に回転合成したカーネルで輝度勾配を求め、
+    /// 中間グレー128を基準に陰影を付ける (画像の外周は中間グレーになる)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `angle_degrees` - 光源の方向 (度、0で右から、90で下から)
+    /// * `depth` - 陰影の深さ
+    pub fn emboss_directional(&mut self, data: &mut [u8], angle_degrees: f32, depth: f32) {
+        console_log!("Applying directional emboss: angle={}, depth={}", angle_degrees, depth);
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let gray = self.gray_buffer(data);
+        let (gradient_x, gradient_y) = self.sobel_gradients(&gray, width, height);
+        let (sin, cos) = angle_degrees.to_radians().sin_cos();
+        
+        for_each_row(&mut data[..width * height * 4], width * 4, |y, row| {
+            for x in 0..width {
+                let idx = x * 4;
+                let value = if x == 0 || y == 0 || x + 1 >= width || y + 1 >= height {
+                    128.0
+                } else {
+                    // 光源方向の勾配 (回転したSobelカーネルの応答)
+                    let gx = gradient_x[y * width + x] as f32;
+                    let gy = gradient_y[y * width + x] as f32;
+                    128.0 + depth * (gx * cos + gy * sin) / 4.0
+                };
+                
+                let v = value.round().clamp(0.0, 255.0) as u8;
+                row[idx] = v;
+                row[idx + 1] = v;
+                row[idx + 2] = v;
+                // Alpha値は保持
+            }
+        });
+    }
+
+    /// 小さなRGBA画像（ロゴ・透かしなど）を指定位置に合成
+    /// オーバーレイのAlpha値で合成し、画像の外にはみ出した部分は切り捨てる
+    /// 
+    /// # Arguments
+    /// * `base` - 合成先のRGBA画像データ (mutable)
+    /// * `overlay` - 合成するRGBA画像データ
+    /// * `ow` - オーバーレイの幅
+    /// * `oh` - オーバーレイの高さ
+    /// * `x` - 合成位置のX座標
+    /// * `y` - 合成位置のY座標
+    /// 
+    /// # Errors
+    /// オーバーレイのデータ長が`ow * oh * 4`と一致しない場合
+    pub fn overlay_image(&mut self, base: &mut [u8], overlay: &[u8], ow: u32, oh: u32, x: u32, y: u32) -> Result<(), JsError> {
+        self.try_overlay_image(base, overlay, ow, oh, x, y).map_err(|e| JsError::new(&e))
+    }
+
+    /// 輝度のみにシャープネス強化を適用
+    /// 輝度チャンネルを「恒等カーネル + amount * ラプラシアン」で強調し、
+    /// 輝度の変化分をRGBに等しく加えることで色差を保つ
+    /// (チャンネルごとのsharpenで起きるエッジの色にじみを防ぐ)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `amount` - シャープネスの強さ (0.0で変化なし)
+    pub fn sharpen_luma(&mut self, data: &mut [u8], amount: f32) {
+        console_log!("Applying luma sharpen with amount: {}", amount);
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let luma: Vec<f32> = data.chunks_exact(4)
+            .map(|p| self.luma(p[0] as f32, p[1] as f32, p[2] as f32))
+            .collect();
+        
+        for y in 1..height.saturating_sub(1) {
+            for x in 1..width.saturating_sub(1) {
+                let i = y * width + x;
+                let laplacian = 4.0 * luma[i] - luma[i - 1] - luma[i + 1] - luma[i - width] - luma[i + width];
+                let delta = amount * laplacian;
+                
+                // 輝度の変化分のみをRGBに加える
+                let idx = i * 4;
+                for c in 0..3 {
+                    data[idx + c] = (data[idx + c] as f32 + delta).round().clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+    }
+
+    /// フィルタを適用してBGRA順で出力
+    /// BGRA順の画素を扱う外部システムとの連携用 (入力データは変更しない)
+    /// 
+    /// 画素単位のフィルタ (Negative, SepiaTone) はフィルタ処理と入れ替えを1回の走査で行う。
+    /// 近傍を参照するフィルタは適用後に別の走査でRとBを入れ替える
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ
+    /// * `filter` - 適用するフィルタ
+    /// 
+    /// # Returns
+    /// フィルタ適用後のBGRA画像データ
+    pub fn process_to_bgra(&self, data: &[u8], filter: Filter) -> Vec<u8> {
+        let mut output = data.to_vec();
+        
+        // 画素単位のフィルタは変換とRとBの入れ替えを1回の走査で行う
+        let pixel_op: Option<fn(u32) -> u32> = match filter {
+            Filter::Negative => Some(|pixel| pixel ^ RGB_MASK),
+            Filter::SepiaTone => Some(sepia_pixel),
+            _ => None,
+        };
+        if let Some(op) = pixel_op {
+            if for_each_pixel_u32(&mut output, |pixel| swap_red_blue(op(pixel))).is_ok() {
+                return output;
+            }
+        }
+        
+        self.sub_processor(self.width, self.height).apply(&mut output, filter);
+        
+        // RとBを入れ替え
+        for pixel in output.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+        output
+    }
+
+    /// 輝度範囲を指定した選択的ブラーを適用
+    /// 元の輝度が`min_luma..=max_luma`に入る画素のみをぼかし、それ以外はそのまま残す
+    /// (シャドウのみ、ハイライトのみの平滑化など)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `radius` - ブラー半径
+    /// * `min_luma` - ぼかす輝度の下限
+    /// * `max_luma` - ぼかす輝度の上限
+    pub fn blur_by_luminance(&mut self, data: &mut [u8], radius: f32, min_luma: u8, max_luma: u8) {
+        console_log!("Applying luminance-masked blur: {}..={}", min_luma, max_luma);
+        
+        let mut blurred = data.to_vec();
+        self.gaussian_blur(&mut blurred, radius);
+        
+        for i in (0..data.len()).step_by(4) {
+            let luma = self.luma(data[i] as f32, data[i + 1] as f32, data[i + 2] as f32) as u8;
+            if (min_luma..=max_luma).contains(&luma) {
+                data[i..i + 3].copy_from_slice(&blurred[i..i + 3]);
+            }
+        }
+    }
+
+    /// グレーのチェッカーボードの上に合成
+    /// 画像編集ソフトと同様に透過部分を可視化する (クロマキーやAlphaのデバッグ用)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ
+    /// * `square` - チェッカーボードのマスの大きさ (0は1として扱う)
+    /// 
+    /// # Returns
+    /// 不透明なRGBA画像データ
+    pub fn composite_over_checkerboard(&self, data: &[u8], square: u32) -> Vec<u8> {
+        let width = self.width as usize;
+        let square = square.max(1) as usize;
+        let mut output = data.to_vec();
+        
+        for (i, pixel) in output.chunks_exact_mut(4).enumerate() {
+            let (x, y) = (i % width, i / width);
+            let background = if (x / square + y / square).is_multiple_of(2) { CHECKER_LIGHT } else { CHECKER_DARK } as f32;
+            let alpha = pixel[3] as f32 / 255.0;
+            
+            for value in pixel[..3].iter_mut() {
+                *value = (*value as f32 * alpha + background * (1.0 - alpha)).round() as u8;
+            }
+            pixel[3] = 255;
+        }
+        
+        output
+    }
+
+    /// 2つの画像の平均二乗誤差 (MSE) を計算
+    /// RGBチャンネルを対象とする (フィルタの回帰テストや変化量の定量化に使用)
+    /// 
+    /// # Arguments
+    /// * `a` - RGBA画像データ
+    /// * `b` - 比較するRGBA画像データ
+    /// 
+    /// # Errors
+    /// データ長が異なる場合
+    pub fn mse(&self, a: &[u8], b: &[u8]) -> Result<f64, JsError> {
+        self.try_mse(a, b).map_err(|e| JsError::new(&e))
+    }
+
+    /// 2つの画像の構造的類似度 (SSIM) を計算
+    /// 輝度を8x8のブロックに分けて計算したSSIMの平均 (1.0で完全一致)
+    /// 
+    /// # Arguments
+    /// * `a` - RGBA画像データ
+    /// * `b` - 比較するRGBA画像データ
+    /// 
+    /// # Errors
+    /// データ長が異なる場合
+    pub fn ssim(&self, a: &[u8], b: &[u8]) -> Result<f64, JsError> {
+        self.try_ssim(a, b).map_err(|e| JsError::new(&e))
+    }
+
+    /// Bayer行列による組織的ディザリングを適用
+    /// 画素ごとに独立して計算でき、誤差拡散と異なり結果は位置のみに依存する
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `levels` - 各チャンネルの階調数 (2以上)
+    /// * `matrix_size` - Bayer行列のサイズ (2, 4, 8)
+    /// 
+    /// # Errors
+    /// `levels`が2未満、または`matrix_size`が2, 4, 8以外の場合
+    pub fn dither_ordered(&mut self, data: &mut [u8], levels: u32, matrix_size: u32) -> Result<(), JsError> {
+        self.try_dither_ordered(data, levels, matrix_size).map_err(|e| JsError::new(&e))
+    }
+
+    /// 万華鏡エフェクトを適用
+    /// 中心まわりの扇形を`segments`個に複製し、隣り合う扇形を鏡映して配置する
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `segments` - 扇形の数 (0と1は変化なし)
+    pub fn kaleidoscope(&mut self, data: &mut [u8], segments: u32) {
+        console_log!("Applying kaleidoscope with segments: {}", segments);
+        
+        if segments <= 1 {
+            return;
+        }
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let temp_data = data.to_vec();
+        
+        let cx = (width as f32 - 1.0) / 2.0;
+        let cy = (height as f32 - 1.0) / 2.0;
+        let wedge = 2.0 * std::f32::consts::PI / segments as f32;
+        
+        for y in 0..height {
+            for x in 0..width {
+                let dx = x as f32 - cx;
+                let dy = y as f32 - cy;
+                let radius = (dx * dx + dy * dy).sqrt();
+                let angle = dy.atan2(dx).rem_euclid(2.0 * std::f32::consts::PI);
+                
+                // 最初の扇形内の角度に変換 (奇数番目の扇形は鏡映)
+                let index = (angle / wedge).floor();
+                let mut local = angle - index * wedge;
+                if index as u32 % 2 == 1 {
+                    local = wedge - local;
+                }
+                
+                let src_x = cx + radius * local.cos();
+                let src_y = cy + radius * local.sin();
+                let color = self.sample_bilinear(&temp_data, src_x, src_y, width, height);
+                let idx = (y * width + x) * 4;
+                for c in 0..4 {
+                    data[idx + c] = color[c].round().clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+    }
+
+    /// 任意の角度で画像を回転
+    /// 中心まわりにバイリニア補間で回転し、元画像の範囲外は背景色で塗りつぶす
+    /// 出力サイズは元画像と同じ (はみ出した部分は切り取られる)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ
+    /// * `degrees` - 回転角度 (度、時計回り)
+    /// * `bg` - 背景色 [r, g, b, a]
+    /// 
+    /// # Returns
+    /// 回転後のRGBA画像データ
+    /// 
+    /// # Errors
+    /// `bg`が4要素でない場合
+    pub fn rotate(&self, data: &[u8], degrees: f32, bg: &[u8]) -> Result<Vec<u8>, JsError> {
+        self.try_rotate(data, degrees, bg).map_err(|e| JsError::new(&e))
+    }
+
+    /// 輝度キー（明るさによる透過）を適用
+    /// 輝度が`low`より暗い、または`high`より明るい画素を透明にする
+    /// (黒背景・白背景の抜き出しに使用)
+    /// データ長が4の倍数でない場合は何もしない
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `low` - これより暗い画素を透明にする
+    /// * `high` - これより明るい画素を透明にする
+    pub fn luma_key(&mut self, data: &mut [u8], low: u8, high: u8) {
+        console_log!("Applying luma key: low={}, high={}", low, high);
+        
+        let result = for_each_pixel_u32(data, |pixel| {
+            let [r, g, b, _] = pixel.to_le_bytes();
+            let luma = self.luma(r as f32, g as f32, b as f32) as u8;
+            if luma < low || luma > high {
+                // Alpha (上位8ビット) のみ0にする
+                pixel & RGB_MASK
+            } else {
+                pixel
+            }
+        });
+        if result.is_err() {
+            console_log!("Skipping luma key: data length is not a multiple of 4");
+        }
+    }
+
+    /// グリッチ（データモッシュ風）エフェクトを適用
+    /// 水平方向の帯をランダムにずらし、RとBのチャンネルを左右にずらす
+    /// 乱数はシードから決定的に生成されるため、同じシードでは同じ結果になる
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `intensity` - 効果の強さ (0.0で変化なし、1.0で最大)
+    /// * `seed` - 乱数のシード
+    pub fn glitch(&mut self, data: &mut [u8], intensity: f32, seed: u32) {
+        console_log!("Applying glitch: intensity={}, seed={}", intensity, seed);
+        
+        let intensity = intensity.clamp(0.0, 1.0);
+        if intensity == 0.0 {
+            return;
+        }
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let temp_data = data.to_vec();
+        let mut rng = XorShift32::new(seed);
+        
+        // チャンネルのずれ量 (Rは右、Bは左へ、最低1ピクセル)
+        let channel_offset = (intensity * width as f32 * 0.02).ceil() as i32;
+        let max_shift = (intensity * width as f32 * 0.1).max(1.0);
+        
+        let mut y = 0;
+        while y < height {
+            // 帯の高さとずれ量をランダムに決定
+            let band_height = 1 + (rng.next_u32() as usize % (height / 8).max(1));
+            let shift = if rng.next_f32() < intensity {
+                ((rng.next_f32() * 2.0 - 1.0) * max_shift).round() as i32
+            } else {
+                0
+            };
+            
+            for row in y..(y + band_height).min(height) {
+                for x in 0..width {
+                    let base_x = x as i32 - shift;
+                    let sample = |offset: i32, c: usize| {
+                        let sx = (base_x + offset).rem_euclid(width as i32) as usize;
+                        temp_data[(row * width + sx) * 4 + c]
+                    };
+                    let idx = (row * width + x) * 4;
+                    data[idx] = sample(-channel_offset, 0);
+                    data[idx + 1] = sample(0, 1);
+                    data[idx + 2] = sample(channel_offset, 2);
+                    data[idx + 3] = sample(0, 3);
+                }
+            }
+            y += band_height;
+        }
+    }
+
+    /// エッジ強調のみを適用（HDR・ポスタリゼーションなし）
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `strength` - エッジ強調の強さ (0.0で変化なし、hdr_animeでは0.5)
+    pub fn edge_enhance(&mut self, data: &mut [u8], strength: f32) {
+        console_log!("Applying edge enhance with strength: {}", strength);
+        let width = self.width as usize;
+        let height = self.height as usize;
+        self.apply_edge_enhancement(data, width, height, strength, EdgeOutline::NONE);
+    }
+
+    /// 画像の代表色（ドミナントカラー）をk-meansで抽出
+    /// 初期値は最遠点選択で決めるため、同じ画像からは常に同じ結果になる
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ
+    /// * `k` - 抽出する色数
+    /// 
+    /// # Returns
+    /// 出現頻度の高い順に並んだRGBの組 (最大 `k * 3` バイト)
+    pub fn dominant_colors(&self, data: &[u8], k: u32) -> Vec<u8> {
+        // 大きな画像は間引いてサンプリング
+        const MAX_SAMPLES: usize = 4096;
+        const ITERATIONS: usize = 10;
+        
+        let pixel_count = data.len() / 4;
+        if k == 0 || pixel_count == 0 {
+            return Vec::new();
+        }
+        
+        let step = pixel_count.div_ceil(MAX_SAMPLES).max(1);
+        let samples: Vec<[f32; 3]> = data
+            .chunks_exact(4)
+            .step_by(step)
+            .map(|p| [p[0] as f32, p[1] as f32, p[2] as f32])
+            .collect();
+        let k = (k as usize).min(samples.len());
+        
+        let distance = |a: &[f32; 3], b: &[f32; 3]| {
+            (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+        };
+        
+        let nearest = |centers: &[[f32; 3]], p: &[f32; 3]| {
+            let mut best = 0;
+            let mut best_distance = f32::MAX;
+            for (i, center) in centers.iter().enumerate() {
+                let d = distance(center, p);
+                if d < best_distance {
+                    best_distance = d;
+                    best = i;
+                }
+            }
+            (best, best_distance)
+        };
+        
+        // 最遠点選択による初期化
+        let mut centers = vec![samples[0]];
+        while centers.len() < k {
+            let (farthest, _) = samples
+                .iter()
+                .map(|p| (p, nearest(&centers, p).1))
+                .fold((&samples[0], -1.0), |acc, cur| if cur.1 > acc.1 { cur } else { acc });
+            centers.push(*farthest);
+        }
+        
+        // Lloydの反復
+        let mut counts = vec![0usize; k];
+        for _ in 0..ITERATIONS {
+            let mut sums = vec![[0.0f32; 3]; k];
+            counts.iter_mut().for_each(|c| *c = 0);
+            
+            for p in &samples {
+                let (i, _) = nearest(&centers, p);
+                for c in 0..3 {
+                    sums[i][c] += p[c];
+                }
+                counts[i] += 1;
+            }
+            
+            for i in 0..k {
+                if counts[i] > 0 {
+                    for c in 0..3 {
+                        centers[i][c] = sums[i][c] / counts[i] as f32;
+                    }
+                }
+            }
+        }
+        
+        // 頻度の高い順に並べる
+        let mut order: Vec<usize> = (0..k).collect();
+        order.sort_by(|&a, &b| counts[b].cmp(&counts[a]));
+        
+        order
+            .iter()
+            .flat_map(|&i| centers[i].map(|v| v.round().clamp(0.0, 255.0) as u8))
+            .collect()
+    }
+
+    /// 各ピクセルを指定パレットの最も近い色に置き換える（ユークリッド距離）
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `palette` - RGBの組を並べたパレット (長さは3の倍数)
+    /// 
+    /// # Errors
+    /// パレットが空、または長さが3の倍数でない場合
+    pub fn quantize_to_palette(&mut self, data: &mut [u8], palette: &[u8]) -> Result<(), JsError> {
+        self.try_quantize_to_palette(data, palette).map_err(|e| JsError::new(&e))
+    }
+
+    /// マスクで画素ごとにブラー半径を変えるブラーを適用
+    /// 深度マップを使った被写界深度表現などに使用
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `mask` - 画素ごとのブラー量 (width * height、0でシャープ、255で`max_radius`)
+    /// * `max_radius` - マスク値255での最大ブラー半径 (`MAX_MASK_BLUR_RADIUS` = 64が上限。
+    ///   半径1ごとにブラーした画像を補間するため、処理時間は半径に比例する)
+    /// 
+    /// # Errors
+    /// `data`の長さが`width * height * 4`、`mask`の長さが`width * height`と一致しない場合
+    pub fn blur_with_mask(&mut self, data: &mut [u8], mask: &[u8], max_radius: f32) -> Result<(), JsError> {
+        self.try_blur_with_mask(data, mask, max_radius).map_err(|e| JsError::new(&e))
+    }
+
+    /// R・G・Bを独立に全範囲へ引き伸ばす自動レベル補正
+    /// チャンネルごとに補正するため色かぶりも補正される (簡易オートホワイトバランス)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    pub fn auto_levels_per_channel(&mut self, data: &mut [u8]) {
+        console_log!("Applying per-channel auto levels");
+        
+        let histograms = channel_histograms(data);
+        
+        for (c, histogram) in histograms.iter().enumerate() {
+            let low = histogram.iter().position(|&n| n > 0);
+            let high = histogram.iter().rposition(|&n| n > 0);
+            let (low, high) = match (low, high) {
+                (Some(low), Some(high)) if high > low => (low as f32, high as f32),
+                // 空の画像や単一値のチャンネルはそのまま
+                _ => continue,
+            };
+            
+            let scale = 255.0 / (high - low);
+            for pixel in data.chunks_exact_mut(4) {
+                pixel[c] = ((pixel[c] as f32 - low) * scale).round() as u8;
+            }
+        }
+    }
+
+    /// 現在のサイズのRGBA画像データをPNGにエンコード
+    /// Canvasを介さずにダウンロードやアップロード用のバイト列を作成できる
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ
+    /// 
+    /// # Errors
+    /// `data`の長さが`width * height * 4`と一致しない場合、またはエンコードに失敗した場合
+    #[cfg(feature = "image")]
+    pub fn encode_png(&self, data: &[u8]) -> Result<Vec<u8>, JsError> {
+        self.try_encode_png(data).map_err(|e| JsError::new(&e))
+    }
+
+    /// 自然な彩度（バイブランス）を適用
+    /// 彩度の低い画素ほど強く、既に彩度の高い画素や肌色は控えめに彩度を上げる
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `amount` - 強調の強さ (0.0で変化なし、負の値で彩度を下げる)
+    pub fn vibrance(&mut self, data: &mut [u8], amount: f32) {
+        console_log!("Applying vibrance with amount: {}", amount);
+        
+        for pixel in data.chunks_exact_mut(4) {
+            self.vibrance_pixel(pixel, amount);
+        }
+    }
+
+    /// コミック調エフェクトを適用
+    /// 色をポスタリゼーションし、Sobelエッジが閾値を超える画素を黒い輪郭線にする
+    /// hdr_animeと異なりHDR強調は行わない
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `levels` - 各チャンネルの階調数 (2以上)
+    /// * `edge_threshold` - 輪郭線とするエッジ強度の閾値
+    /// 
+    /// # Errors
+    /// `levels`が2未満の場合
+    pub fn comic(&mut self, data: &mut [u8], levels: u32, edge_threshold: u8) -> Result<(), JsError> {
+        self.try_comic(data, levels, edge_threshold).map_err(|e| JsError::new(&e))
+    }
+
+    /// 画像の半分を反対側に鏡映コピーして左右（上下）対称にする
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `axis` - 対称軸 (0: 左半分を右へ、1: 上半分を下へ)
+    /// 
+    /// # Errors
+    /// `axis`が0または1でない場合
+    pub fn symmetry(&mut self, data: &mut [u8], axis: u32) -> Result<(), JsError> {
+        self.try_symmetry(data, axis).map_err(|e| JsError::new(&e))
+    }
+
+    /// ノイズ除去とシャープ化を組み合わせたディテール強調を適用
+    /// 3x3メディアンでノイズを取り除いてからアンシャープマスクをかけるため、
+    /// ノイズを強調せずに輪郭だけを際立たせる
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    pub fn enhance_detail(&mut self, data: &mut [u8]) {
+        console_log!("Applying enhance detail");
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let mut scratch = data.to_vec();
+        
+        // メディアンによるノイズ除去
+        for y in 0..height {
+            for x in 0..width {
+                let idx = (y * width + x) * 4;
+                let median = self.median3x3(&scratch, x, y, width, height);
+                data[idx..idx + 3].copy_from_slice(&median);
+            }
+        }
+        
+        // 同じ作業バッファでノイズ除去後の画像にアンシャープマスク
+        scratch.copy_from_slice(data);
+        for y in 0..height {
+            for x in 0..width {
+                let idx = (y * width + x) * 4;
+                for c in 0..3 {
+                    let mut sum = 0.0;
+                    for ky in -1..=1 {
+                        for kx in -1..=1 {
+                            let px = (x as i32 + kx).clamp(0, width as i32 - 1) as usize;
+                            let py = (y as i32 + ky).clamp(0, height as i32 - 1) as usize;
+                            sum += scratch[(py * width + px) * 4 + c] as f32;
+                        }
+                    }
+                    let value = scratch[idx + c] as f32;
+                    let sharpened = value + ENHANCE_DETAIL_AMOUNT * (value - sum / 9.0);
+                    data[idx + c] = sharpened.round().clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+    }
+
+    /// 粗い輝度マップによる局所的な露出補正（簡易ローカルトーンマッピング）を適用
+    /// 周囲が暗い領域を明るく、周囲が明るい領域を暗くするため、逆光の被写体などに有効
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `strength` - 補正の強さ (0.0で変化なし、1.0で局所平均を中間グレーに揃える)
+    pub fn local_exposure(&mut self, data: &mut [u8], strength: f32) {
+        console_log!("Applying local exposure with strength: {}", strength);
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        if width == 0 || height == 0 {
+            return;
+        }
+        
+        // 縮小した輝度マップ (各セルの平均輝度)
+        let grid_w = LOCAL_EXPOSURE_GRID.min(width);
+        let grid_h = LOCAL_EXPOSURE_GRID.min(height);
+        let mut sums = vec![0.0f32; grid_w * grid_h];
+        let mut counts = vec![0u32; grid_w * grid_h];
+        for y in 0..height {
+            for x in 0..width {
+                let idx = (y * width + x) * 4;
+                let cell = (y * grid_h / height) * grid_w + x * grid_w / width;
+                sums[cell] += self.luma(data[idx] as f32, data[idx + 1] as f32, data[idx + 2] as f32);
+                counts[cell] += 1;
+            }
+        }
+        let map: Vec<f32> = sums.iter().zip(&counts).map(|(&s, &n)| s / n.max(1) as f32).collect();
+        
+        // セル中心を基準にバイリニア補間で拡大し、局所平均を中間グレーへ寄せる
+        for y in 0..height {
+            let fy = ((y as f32 + 0.5) * grid_h as f32 / height as f32 - 0.5).clamp(0.0, grid_h as f32 - 1.0);
+            let y0 = fy.floor() as usize;
+            let y1 = (y0 + 1).min(grid_h - 1);
+            let ty = fy - y0 as f32;
+            
+            for x in 0..width {
+                let fx = ((x as f32 + 0.5) * grid_w as f32 / width as f32 - 0.5).clamp(0.0, grid_w as f32 - 1.0);
+                let x0 = fx.floor() as usize;
+                let x1 = (x0 + 1).min(grid_w - 1);
+                let tx = fx - x0 as f32;
+                
+                let top = map[y0 * grid_w + x0] * (1.0 - tx) + map[y0 * grid_w + x1] * tx;
+                let bottom = map[y1 * grid_w + x0] * (1.0 - tx) + map[y1 * grid_w + x1] * tx;
+                let local_mean = top * (1.0 - ty) + bottom * ty;
+                let offset = strength * (128.0 - local_mean);
+                
+                let idx = (y * width + x) * 4;
+                for c in 0..3 {
+                    data[idx + c] = (data[idx + c] as f32 + offset).round().clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+    }
+
+    /// 指定座標の画素をRGBAで取得
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ
+    /// * `x`, `y` - 画素の座標
+    /// 
+    /// # Returns
+    /// 4バイトのRGBA
+    /// 
+    /// # Errors
+    /// 座標が画像の範囲外、または`data`が短すぎる場合
+    pub fn get_pixel(&self, data: &[u8], x: u32, y: u32) -> Result<Vec<u8>, JsError> {
+        self.try_get_pixel(data, x, y).map_err(|e| JsError::new(&e))
+    }
+
+    /// 指定座標の画素をRGBAで設定
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `x`, `y` - 画素の座標
+    /// * `rgba` - 設定する色 (4バイト)
+    /// 
+    /// # Errors
+    /// 座標が画像の範囲外、`data`が短すぎる、または`rgba`が4バイトでない場合
+    pub fn set_pixel(&self, data: &mut [u8], x: u32, y: u32, rgba: &[u8]) -> Result<(), JsError> {
+        self.try_set_pixel(data, x, y, rgba).map_err(|e| JsError::new(&e))
+    }
+
+    /// 輝度をサーモグラフィ風の疑似カラーに変換
+    /// 暗い画素ほど青（冷たい）、明るい画素ほど赤（熱い）になる
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    pub fn thermal(&mut self, data: &mut [u8]) {
+        console_log!("Applying thermal map");
+        
+        let lut = interpolate_gradient(&THERMAL_COLORS);
+        self.apply_gradient_lut(data, &lut);
+    }
+
+    /// 2フレーム間の差分を強調（動き検出の基礎）
+    /// いずれかのチャンネルが`threshold`を超えて変化した画素のみ残し、それ以外は黒にする
+    /// 
+    /// # Arguments
+    /// * `prev` - 前フレームのRGBA画像データ
+    /// * `curr` - 現フレームのRGBA画像データ (mutable)
+    /// * `threshold` - 変化とみなすチャンネル値の差
+    /// 
+    /// # Errors
+    /// 2つのバッファの長さが異なる場合
+    pub fn frame_difference(&self, prev: &[u8], curr: &mut [u8], threshold: u8) -> Result<(), JsError> {
+        self.try_frame_difference(prev, curr, threshold).map_err(|e| JsError::new(&e))
+    }
+
+    /// 前フレームとのブレンドによる時間方向のノイズ除去を適用
+    /// 暗所でのちらつきを抑える。結果は次のフレーム用に保持される
+    /// (初回やサイズが変わった場合はそのフレームを保持するだけで変化なし)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `alpha` - 前フレームの重み (0.0で変化なし、1.0に近いほど強く平滑化)
+    pub fn denoise_temporal(&mut self, data: &mut [u8], alpha: f32) {
+        console_log!("Applying temporal denoise with alpha: {}", alpha);
+        
+        if self.previous_frame.len() == data.len() {
+            let alpha = alpha.clamp(0.0, 1.0);
+            for (value, &previous) in data.iter_mut().zip(&self.previous_frame) {
+                *value = (previous as f32 * alpha + *value as f32 * (1.0 - alpha)).round() as u8;
+            }
+        }
+        
+        self.previous_frame.clear();
+        self.previous_frame.extend_from_slice(data);
+    }
+
+    /// 時間方向の処理で保持している前フレームを破棄
+    /// カメラの切り替えやシーンチェンジの際に呼び出す
+    pub fn reset_previous_frame(&mut self) {
+        self.previous_frame.clear();
+    }
+
+    /// 縦横比を保ったまま縮小したサムネイルを生成（ボックス平均）
+    /// 長辺が`max_dim`になるように縮小する (元画像の方が小さい場合は拡大しない)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ
+    /// * `max_dim` - サムネイルの長辺のピクセル数 (0は1として扱う)
+    /// 
+    /// # Returns
+    /// サムネイルのRGBA画像データ (サイズは`thumbnail_dimensions`で取得)
+    pub fn thumbnail(&self, data: &[u8], max_dim: u32) -> Vec<u8> {
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let (thumb_width, thumb_height) = self.thumbnail_size(max_dim);
+        let mut output = vec![0u8; thumb_width * thumb_height * 4];
+        
+        for ty in 0..thumb_height {
+            let y0 = ty * height / thumb_height;
+            let y1 = ((ty + 1) * height / thumb_height).max(y0 + 1);
+            for tx in 0..thumb_width {
+                let x0 = tx * width / thumb_width;
+                let x1 = ((tx + 1) * width / thumb_width).max(x0 + 1);
+                
+                // 対応する元画像の矩形の平均
+                let mut sum = [0u32; 4];
+                for y in y0..y1 {
+                    for x in x0..x1 {
+                        let idx = (y * width + x) * 4;
+                        for c in 0..4 {
+                            sum[c] += data[idx + c] as u32;
+                        }
+                    }
+                }
+                let count = ((y1 - y0) * (x1 - x0)) as u32;
+                let out = (ty * thumb_width + tx) * 4;
+                for c in 0..4 {
+                    output[out + c] = ((sum[c] + count / 2) / count) as u8;
+                }
+            }
+        }
+        
+        output
+    }
+
+    /// thumbnailの出力サイズを取得
+    /// 
+    /// # Arguments
+    /// * `max_dim` - サムネイルの長辺のピクセル数
+    /// 
+    /// # Returns
+    /// [幅, 高さ]
+    pub fn thumbnail_dimensions(&self, max_dim: u32) -> Vec<u32> {
+        let (thumb_width, thumb_height) = self.thumbnail_size(max_dim);
+        vec![thumb_width as u32, thumb_height as u32]
+    }
+
+    /// ピクセルソート（グリッチアート）エフェクトを適用
+    /// 輝度が`threshold`を超える画素が連続する区間を、行（または列）ごとに輝度順に並べ替える
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `threshold` - 並べ替えの対象とする輝度の下限 (これを超える画素が対象)
+    /// * `horizontal` - trueで行ごと、falseで列ごとに並べ替える
+    pub fn pixel_sort(&mut self, data: &mut [u8], threshold: u8, horizontal: bool) {
+        console_log!("Applying pixel sort: threshold={}, horizontal={}", threshold, horizontal);
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        
+        // (ライン数, 1ラインの画素数, ライン間の刻み, 画素間の刻み) ※刻みは画素単位
+        let (lines, length, line_stride, pixel_stride) = if horizontal {
+            (height, width, width, 1)
+        } else {
+            (width, height, 1, width)
+        };
+        
+        let mut run: Vec<[u8; 4]> = Vec::with_capacity(length);
+        for line in 0..lines {
+            let index_at = |i: usize| (line * line_stride + i * pixel_stride) * 4;
+            
+            let mut i = 0;
+            while i < length {
+                // 閾値を超える連続区間を探す
+                let start = i;
+                run.clear();
+                while i < length {
+                    let idx = index_at(i);
+                    let luma = self.luma(data[idx] as f32, data[idx + 1] as f32, data[idx + 2] as f32);
+                    if luma <= threshold as f32 {
+                        break;
+                    }
+                    run.push([data[idx], data[idx + 1], data[idx + 2], data[idx + 3]]);
+                    i += 1;
+                }
+                
+                if run.len() > 1 {
+                    run.sort_by(|a, b| {
+                        let la = self.luma(a[0] as f32, a[1] as f32, a[2] as f32);
+                        let lb = self.luma(b[0] as f32, b[1] as f32, b[2] as f32);
+                        la.total_cmp(&lb)
+                    });
+                    for (k, pixel) in run.iter().enumerate() {
+                        let idx = index_at(start + k);
+                        data[idx..idx + 4].copy_from_slice(pixel);
+                    }
+                }
+                
+                // 閾値以下の画素は読み飛ばす
+                if i == start {
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    /// 輝度ヒストグラムから指定パーセンタイルの輝度を取得
+    /// 露出の判定 (95パーセンタイルが白飛びしているか等) に使用
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ
+    /// * `percentile` - パーセンタイル (0.0..100.0、範囲外はクランプ)
+    /// 
+    /// # Returns
+    /// 指定パーセンタイルの輝度 (空の画像では0)
+    pub fn luma_percentile(&self, data: &[u8], percentile: f32) -> u8 {
+        let histogram = self.luma_histogram(data);
+        let total: u32 = histogram.iter().sum();
+        if total == 0 {
+            return 0;
+        }
+        
+        // 累積度数が目標数に達する最初の輝度
+        let target = ((percentile.clamp(0.0, 100.0) / 100.0 * total as f32).ceil() as u32).max(1);
+        let mut cumulative = 0;
+        for (value, &count) in histogram.iter().enumerate() {
+            cumulative += count;
+            if cumulative >= target {
+                return value as u8;
+            }
+        }
+        255
+    }
+
+    /// 適応的二値化を適用
+    /// 各画素を周囲のブロックの平均輝度から`c`を引いた値と比較するため、
+    /// 照明にむらがあり単一の閾値では二値化できない画像でもきれいに分離できる
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `block_size` - 平均を取るブロックの一辺 (0は1として扱う)
+    /// * `c` - 平均輝度から差し引く定数
+    pub fn adaptive_threshold(&mut self, data: &mut [u8], block_size: u32, c: i32) {
+        console_log!("Applying adaptive threshold: block_size={}, c={}", block_size, c);
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let half = (block_size.max(1) / 2) as usize;
+        let gray = self.gray_buffer(data);
+        
+        // 積分画像でブロック平均をO(1)で求める
+        let stride = width + 1;
+        let mut integral = vec![0u64; stride * (height + 1)];
+        for y in 0..height {
+            let mut row_sum = 0u64;
+            for x in 0..width {
+                row_sum += gray[y * width + x] as u64;
+                integral[(y + 1) * stride + x + 1] = integral[y * stride + x + 1] + row_sum;
+            }
+        }
+        
+        for y in 0..height {
+            let y0 = y.saturating_sub(half);
+            let y1 = (y + half + 1).min(height);
+            for x in 0..width {
+                let x0 = x.saturating_sub(half);
+                let x1 = (x + half + 1).min(width);
+                
+                let sum = integral[y1 * stride + x1] + integral[y0 * stride + x0]
+                    - integral[y0 * stride + x1]
+                    - integral[y1 * stride + x0];
+                let count = ((y1 - y0) * (x1 - x0)) as f32;
+                let threshold = sum as f32 / count - c as f32;
+                
+                let value = if gray[y * width + x] as f32 > threshold { 255 } else { 0 };
+                let idx = (y * width + x) * 4;
+                data[idx..idx + 3].fill(value);
+                // Alpha値は保持
+            }
+        }
+    }
+
+    /// ハイパスフィルタを適用（元画像 - ぼかした画像 + 128）
+    /// 周波数分離によるレタッチの基礎となる。平坦な領域は中間グレー128になる
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `radius` - 低周波成分を求めるブラー半径
+    pub fn high_pass(&mut self, data: &mut [u8], radius: f32) {
+        console_log!("Applying high pass with radius: {}", radius);
+        
+        let mut blurred = data.to_vec();
+        self.gaussian_blur(&mut blurred, radius);
+        
+        for (pixel, low) in data.chunks_exact_mut(4).zip(blurred.chunks_exact(4)) {
+            for c in 0..3 {
+                let detail = pixel[c] as i32 - low[c] as i32 + 128;
+                pixel[c] = detail.clamp(0, 255) as u8;
+            }
+            // Alpha値は保持
+        }
+    }
+
+    /// 周波数分離を行い、低周波（ぼかし）レイヤーと高周波（ディテール）レイヤーを生成
+    /// 高周波レイヤーは差分を1/2にして128を加えた値で保存される
+    /// (`recombine_frequency`で元画像を±1の誤差で復元できる)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ
+    /// * `radius` - 低周波成分を求めるブラー半径
+    /// 
+    /// # Returns
+    /// 低周波レイヤーと高周波レイヤーを連結したRGBA画像データ
+    /// (前半`data.len()`バイトが低周波、後半が高周波)
+    pub fn frequency_separation(&self, data: &[u8], radius: f32) -> Vec<u8> {
+        let mut output = data.to_vec();
+        self.sub_processor(self.width, self.height).gaussian_blur(&mut output, radius);
+        output.extend_from_slice(data);
+        
+        let (low, high) = output.split_at_mut(data.len());
+        for (high_pixel, low_pixel) in high.chunks_exact_mut(4).zip(low.chunks_exact(4)) {
+            for c in 0..3 {
+                let detail = (high_pixel[c] as i32 - low_pixel[c] as i32) / 2 + 128;
+                high_pixel[c] = detail.clamp(0, 255) as u8;
+            }
+            // Alpha値は元画像のまま
+        }
+        
+        output
+    }
+
+    /// 周波数分離した2つのレイヤーを合成して画像に戻す
+    /// 
+    /// # Arguments
+    /// * `low` - 低周波レイヤーのRGBA画像データ
+    /// * `high` - 高周波レイヤーのRGBA画像データ
+    /// 
+    /// # Returns
+    /// 合成したRGBA画像データ (Alphaは低周波レイヤーの値)
+    /// 
+    /// # Errors
+    /// 2つのバッファの長さが異なる場合
+    pub fn recombine_frequency(&self, low: &[u8], high: &[u8]) -> Result<Vec<u8>, JsError> {
+        self.try_recombine_frequency(low, high).map_err(|e| JsError::new(&e))
+    }
+
+    /// リニア空間（ガンマ補正を外した状態）でガウシアンブラーを適用
+    /// sRGBのままぼかすと明るい色どうしの境界が暗く沈むため、
+    /// sRGBの伝達関数で線形化してからぼかし、再びsRGBに戻す
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `radius` - ブラー半径
+    pub fn gaussian_blur_linear(&mut self, data: &mut [u8], radius: f32) {
+        console_log!("Applying linear-space gaussian blur with radius: {}", radius);
+        
+        if radius <= 0.0 {
+            return;
+        }
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        
+        // 8bitに戻すと暗部の階調が失われるため浮動小数点のままぼかす
+        let mut linear: Vec<f32> = data[..width * height * 4]
+            .chunks_exact(4)
+            .flat_map(|p| [p[0], p[1], p[2]])
+            .map(srgb_to_linear)
+            .collect();
+        
+        self.box_blur_f32(&mut linear, width, height, 3, radius);
+        
+        for (pixel, rgb) in data.chunks_exact_mut(4).zip(linear.chunks_exact(3)) {
+            for c in 0..3 {
+                pixel[c] = linear_to_srgb(rgb[c]);
+            }
+            // Alpha値は保持
+        }
+    }
+
+    /// ビネット（周辺減光）エフェクトを適用
+    /// 中心から端までを-1.0..1.0に正規化した距離が`radius`を超えた位置から徐々に暗くする
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `radius` - 減光が始まる正規化距離 (0.0..1.0)
+    /// * `strength` - 画像端での減光の強さ (0.0で変化なし、1.0で黒)
+    /// * `shape` - 減光の形状
+    pub fn vignette(&mut self, data: &mut [u8], radius: f32, strength: f32, shape: VignetteShape) {
+        console_log!("Applying vignette: radius={}, strength={}, shape={:?}", radius, strength, shape);
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let radius = radius.clamp(0.0, 1.0);
+        let half_w = width as f32 / 2.0;
+        let half_h = height as f32 / 2.0;
+        
+        for y in 0..height {
+            let ny = (y as f32 + 0.5 - half_h) / half_h;
+            for x in 0..width {
+                let nx = (x as f32 + 0.5 - half_w) / half_w;
+                let distance = match shape {
+                    VignetteShape::Elliptical => (nx * nx + ny * ny).sqrt(),
+                    VignetteShape::Rectangular => nx.abs().max(ny.abs()),
+                };
+                
+                if distance <= radius {
+                    continue;
+                }
+                
+                // 減光開始位置から端までを滑らかに暗くする
+                let t = ((distance - radius) / (1.0 - radius).max(f32::EPSILON)).min(1.0);
+                let factor = 1.0 - strength * t * t;
+                
+                let idx = (y * width + x) * 4;
+                for c in 0..3 {
+                    data[idx + c] = (data[idx + c] as f32 * factor).round().clamp(0.0, 255.0) as u8;
+                }
+                // Alpha値は保持
+            }
+        }
+    }
+
+    /// 色紙に描いた鉛筆画風エフェクトを適用
+    /// 鉛筆画の明るさを`ink`（暗部）から`paper`（明部）の間の色に割り当てる
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `paper` - 紙の色 [r, g, b]
+    /// * `ink` - 線の色 [r, g, b]
+    /// 
+    /// # Errors
+    /// `paper`または`ink`が3要素でない場合
+    pub fn colored_sketch(&mut self, data: &mut [u8], paper: &[u8], ink: &[u8]) -> Result<(), JsError> {
+        self.try_colored_sketch(data, paper, ink).map_err(|e| JsError::new(&e))
+    }
+
+    /// 小さな画像を繰り返し並べて現在のサイズの画像を生成
+    /// テストパターンや合成用の背景の作成に使用
+    /// 
+    /// # Arguments
+    /// * `src` - 並べる元画像のRGBAデータ
+    /// * `sw` - 元画像の幅
+    /// * `sh` - 元画像の高さ
+    /// 
+    /// # Returns
+    /// `width * height`のRGBA画像データ
+    /// 
+    /// # Errors
+    /// 元画像のサイズが0、または`src`の長さが`sw * sh * 4`と一致しない場合
+    pub fn tile_fill(&self, src: &[u8], sw: u32, sh: u32) -> Result<Vec<u8>, JsError> {
+        self.try_tile_fill(src, sw, sh).map_err(|e| JsError::new(&e))
+    }
+
+    /// 元画像の上にエッジを指定色で描画
+    /// `edge_detection`と異なり、エッジ以外の画素は元の色のまま残す
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `color` - エッジの色 [r, g, b]
+    /// * `threshold` - エッジとみなすSobel強度の閾値
+    /// 
+    /// # Errors
+    /// `color`が3要素でない場合
+    pub fn edge_overlay(&mut self, data: &mut [u8], color: &[u8], threshold: u8) -> Result<(), JsError> {
+        self.try_edge_overlay(data, color, threshold).map_err(|e| JsError::new(&e))
+    }
+
+    /// 境界を鏡像反転で補うガウシアンブラーを適用
+    /// 通常の`gaussian_blur`は画像外のサンプルを除いて平均するため端の画素が内側の色に引っ張られるが、
+    /// こちらは画像の外側を鏡像で補い、すべての画素で完全なカーネルを使う
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `radius` - ブラー半径
+    pub fn gaussian_blur_reflect(&mut self, data: &mut [u8], radius: f32) {
+        console_log!("Applying reflect-border gaussian blur with radius: {}", radius);
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        if radius <= 0.0 || width == 0 || height == 0 {
+            return;
+        }
+        
+        // カーネルが届く範囲だけ鏡像で拡張した画像をぼかし、元の範囲を切り出す
+        let pad = (radius.ceil() as usize).min(width.max(height));
+        let padded_width = width + 2 * pad;
+        let padded_height = height + 2 * pad;
+        let mut padded = vec![0u8; padded_width * padded_height * 4];
+        for py in 0..padded_height {
+            let sy = reflect_index(py as i64 - pad as i64, height);
+            for px in 0..padded_width {
+                let sx = reflect_index(px as i64 - pad as i64, width);
+                let src = (sy * width + sx) * 4;
+                let dst = (py * padded_width + px) * 4;
+                padded[dst..dst + 4].copy_from_slice(&data[src..src + 4]);
+            }
+        }
+        
+        self.sub_processor(padded_width as u32, padded_height as u32)
+            .gaussian_blur(&mut padded, radius);
+        
+        for y in 0..height {
+            let src = ((y + pad) * padded_width + pad) * 4;
+            let dst = y * width * 4;
+            data[dst..dst + width * 4].copy_from_slice(&padded[src..src + width * 4]);
+        }
+    }
+
+    /// JSONで記述したフィルタの列を順番に適用
+    /// プリセットを宣言的に保存・再生する用途に使用する
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `ops_json` - `[{ "filter": "gaussian_blur", "params": { "radius": 2.0 } }, ...]` 形式のJSON
+    ///   (`params`は省略可能で、省略したパラメータはデフォルト値)
+    /// 
+    /// # Errors
+    /// JSONが不正な場合、未知のフィルタ名やパラメータの型・値が不正な場合
+    /// (すべての操作を検証してから適用するため、エラー時は`data`を変更しない)
+    pub fn apply_ops(&mut self, data: &mut [u8], ops_json: &str) -> Result<(), JsError> {
+        self.try_apply_ops(data, ops_json).map_err(|e| JsError::new(&e))
+    }
+
+    /// バイリニア補間で画像をリサイズ
+    /// 縮小時にsRGBのまま平均すると暗くなるため、`linear`を指定するとリニア空間で補間する
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ
+    /// * `new_width` - 出力の幅
+    /// * `new_height` - 出力の高さ
+    /// * `linear` - trueでリニア空間 (sRGBの伝達関数を外した状態) で補間
+    /// 
+    /// # Returns
+    /// `new_width * new_height`のRGBA画像データ
+    pub fn resize_bilinear(&self, data: &[u8], new_width: u32, new_height: u32, linear: bool) -> Vec<u8> {
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let new_width = new_width as usize;
+        let new_height = new_height as usize;
+        if width == 0 || height == 0 {
+            return vec![0u8; new_width * new_height * 4];
+        }
+        
+        let scale_x = width as f32 / new_width.max(1) as f32;
+        let scale_y = height as f32 / new_height.max(1) as f32;
+        
+        // リニア空間ではRGBを線形化したバッファから補間する (Alphaはそのまま)
+        let linear_data: Vec<f32> = if linear {
+            data.chunks_exact(4)
+                .flat_map(|p| [srgb_to_linear(p[0]), srgb_to_linear(p[1]), srgb_to_linear(p[2]), p[3] as f32])
+                .collect()
+        } else {
+            Vec::new()
+        };
+        
+        let mut output = vec![0u8; new_width * new_height * 4];
+        for y in 0..new_height {
+            // 出力画素の中心に対応する入力座標
+            let fy = (y as f32 + 0.5) * scale_y - 0.5;
+            for x in 0..new_width {
+                let fx = (x as f32 + 0.5) * scale_x - 0.5;
+                let idx = (y * new_width + x) * 4;
+                
+                if linear {
+                    let color = self.sample_bilinear(&linear_data, fx, fy, width, height);
+                    for c in 0..3 {
+                        output[idx + c] = linear_to_srgb(color[c]);
+                    }
+                    output[idx + 3] = color[3].round() as u8;
+                } else {
+                    let color = self.sample_bilinear(data, fx, fy, width, height);
+                    for c in 0..4 {
+                        output[idx + c] = color[c].round() as u8;
+                    }
+                }
+            }
+        }
+        
+        output
+    }
+
+    /// 矩形領域の平均色を取得
+    /// 既知のグレーパッチからのホワイトバランス推定やUI用の色サンプリングに使用
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ
+    /// * `x`, `y` - 領域の左上の座標
+    /// * `w`, `h` - 領域の幅と高さ
+    /// 
+    /// # Returns
+    /// 平均のRGBA (4バイト)
+    /// 
+    /// # Errors
+    /// 領域が空、または画像の範囲外にはみ出す場合
+    pub fn average_color(&self, data: &[u8], x: u32, y: u32, w: u32, h: u32) -> Result<Vec<u8>, JsError> {
+        self.try_average_color(data, x, y, w, h).map_err(|e| JsError::new(&e))
+    }
+
+    /// グレーワールド仮説による自動ホワイトバランスを適用
+    /// 画像全体の平均色は無彩色であると仮定し、R・G・Bの平均が等しくなるよう各チャンネルを拡大縮小する
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    pub fn auto_white_balance(&mut self, data: &mut [u8]) {
+        console_log!("Applying gray-world auto white balance");
+        
+        let pixel_count = data.len() / 4;
+        if pixel_count == 0 {
+            return;
+        }
+        
+        let mut sums = [0u64; 3];
+        for pixel in data.chunks_exact(4) {
+            for c in 0..3 {
+                sums[c] += pixel[c] as u64;
+            }
+        }
+        let means = sums.map(|s| s as f32 / pixel_count as f32);
+        let target = (means[0] + means[1] + means[2]) / 3.0;
+        
+        // 平均が0のチャンネルは拡大できないためそのまま
+        let gains = means.map(|m| if m > 0.0 { target / m } else { 1.0 });
+        for pixel in data.chunks_exact_mut(4) {
+            for c in 0..3 {
+                pixel[c] = (pixel[c] as f32 * gains[c]).round().clamp(0.0, 255.0) as u8;
+            }
+            // Alpha値は保持
+        }
+    }
+
+    /// 前フレームを減衰させて重ねる残像（ゴーストトレイル）エフェクトを適用
+    /// 減衰させた前フレームと現フレームのうち明るい方を残すため、動く明るい物体が尾を引く
+    /// 結果は次のフレーム用に保持される (`denoise_temporal`と同じ前フレームを共有する)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `decay` - 1フレームごとに残像に掛ける係数 (0.0で残像なし、1.0に近いほど長く残る)
+    pub fn ghost_trail(&mut self, data: &mut [u8], decay: f32) {
+        console_log!("Applying ghost trail with decay: {}", decay);
+        
+        if self.previous_frame.len() == data.len() {
+            let decay = decay.clamp(0.0, 1.0);
+            for (pixel, previous) in data.chunks_exact_mut(4).zip(self.previous_frame.chunks_exact(4)) {
+                for c in 0..3 {
+                    let ghost = (previous[c] as f32 * decay).round() as u8;
+                    pixel[c] = pixel[c].max(ghost);
+                }
+                // Alpha値は現フレームのまま
+            }
+        }
+        
+        self.previous_frame.clear();
+        self.previous_frame.extend_from_slice(data);
+    }
+
+    /// 内接円の外側を透明にする円形マスクを適用（丸いアバター用の切り抜き）
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `feather` - 円の縁をぼかす幅 (ピクセル、0以下でくっきりした縁)
+    pub fn circular_mask(&mut self, data: &mut [u8], feather: f32) {
+        console_log!("Applying circular mask with feather: {}", feather);
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let cx = width as f32 / 2.0;
+        let cy = height as f32 / 2.0;
+        let radius = cx.min(cy);
+        
+        for y in 0..height {
+            for x in 0..width {
+                let dx = x as f32 + 0.5 - cx;
+                let dy = y as f32 + 0.5 - cy;
+                let inside = radius - (dx * dx + dy * dy).sqrt();
+                
+                // 縁から内側へ`feather`ピクセルかけて不透明にする
+                let coverage = if feather > 0.0 {
+                    (inside / feather).clamp(0.0, 1.0)
+                } else if inside >= 0.0 {
+                    1.0
+                } else {
+                    0.0
+                };
+                
+                let idx = (y * width + x) * 4 + 3;
+                data[idx] = (data[idx] as f32 * coverage).round() as u8;
+            }
+        }
+    }
+
+    /// 輝度を3色に割り当てるトライトーンエフェクトを適用
+    /// 輝度が`low`未満はシャドウ色、`high`を超えるとハイライト色、その間は中間色になる
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `shadow` - シャドウの色 [r, g, b]
+    /// * `mid` - 中間調の色 [r, g, b]
+    /// * `highlight` - ハイライトの色 [r, g, b]
+    /// * `low` - シャドウとみなす輝度の上限 (この値未満)
+    /// * `high` - ハイライトとみなす輝度の下限 (この値より大きい)
+    /// 
+    /// # Errors
+    /// 色が3要素でない場合、または`low`が`high`より大きい場合
+    pub fn tritone(&mut self, data: &mut [u8], shadow: &[u8], mid: &[u8], highlight: &[u8], low: u8, high: u8) -> Result<(), JsError> {
+        self.try_tritone(data, shadow, mid, highlight, low, high).map_err(|e| JsError::new(&e))
+    }
+
+    /// ピントの鋭さ（フォーカス指標）を計算
+    /// グレースケールのラプラシアンの分散を返す。ぼけた画像ほど小さくなるため、
+    /// ピンボケの警告などに使用できる
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ
+    /// 
+    /// # Returns
+    /// ラプラシアンの分散 (3x3未満の画像では0.0)
+    pub fn sharpness_score(&self, data: &[u8]) -> f64 {
+        let width = self.width as usize;
+        let height = self.height as usize;
+        if width < 3 || height < 3 {
+            return 0.0;
+        }
+        
+        let gray = self.gray_buffer(data);
+        let mut sum = 0.0f64;
+        let mut sum_sq = 0.0f64;
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                let i = y * width + x;
+                let laplacian = 4 * gray[i] as i32
+                    - gray[i - 1] as i32
+                    - gray[i + 1] as i32
+                    - gray[i - width] as i32
+                    - gray[i + width] as i32;
+                sum += laplacian as f64;
+                sum_sq += (laplacian * laplacian) as f64;
+            }
+        }
+        
+        let count = ((width - 2) * (height - 2)) as f64;
+        let mean = sum / count;
+        sum_sq / count - mean * mean
+    }
+
+    /// モザイク（ピクセレート）エフェクトを適用
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `block_size` - ブロックの一辺のピクセル数 (0は1として扱う)
+    /// * `mode` - ブロックを塗りつぶす色の決め方
+    pub fn pixelate(&mut self, data: &mut [u8], block_size: u32, mode: PixelateMode) {
+        console_log!("Applying pixelate: block_size={}, mode={:?}", block_size, mode);
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let block = block_size.max(1) as usize;
+        let mut counts: HashMap<[u8; 4], usize> = HashMap::new();
+        
+        for by in (0..height).step_by(block) {
+            for bx in (0..width).step_by(block) {
+                let rows = by..(by + block).min(height);
+                let cols = bx..(bx + block).min(width);
+                
+                let color = match mode {
+                    PixelateMode::Average => {
+                        let mut sum = [0u32; 4];
+                        for y in rows.clone() {
+                            for x in cols.clone() {
+                                let idx = (y * width + x) * 4;
+                                for c in 0..4 {
+                                    sum[c] += data[idx + c] as u32;
+                                }
+                            }
+                        }
+                        let count = (rows.len() * cols.len()) as u32;
+                        sum.map(|s| ((s + count / 2) / count) as u8)
+                    }
+                    PixelateMode::MostFrequent => {
+                        // 同数の場合は先に最多に達した色を採用
+                        counts.clear();
+                        let mut best = [0u8; 4];
+                        let mut best_count = 0;
+                        for y in rows.clone() {
+                            for x in cols.clone() {
+                                let idx = (y * width + x) * 4;
+                                let pixel = [data[idx], data[idx + 1], data[idx + 2], data[idx + 3]];
+                                let count = counts.entry(pixel).or_insert(0);
+                                *count += 1;
+                                if *count > best_count {
+                                    best_count = *count;
+                                    best = pixel;
+                                }
+                            }
+                        }
+                        best
+                    }
+                };
+                
+                for y in rows {
+                    for x in cols.clone() {
+                        let idx = (y * width + x) * 4;
+                        data[idx..idx + 4].copy_from_slice(&color);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 被写体をマスクで残して背景をぼかすポートレート（ボケ）エフェクトを適用
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `mask` - 被写体マスク (width * height、255で被写体、0で背景、中間値は境界のぼかし)
+    /// * `radius` - 背景のブラー半径
+    /// 
+    /// # Errors
+    /// `data`の長さが`width * height * 4`、`mask`の長さが`width * height`と一致しない場合
+    pub fn portrait_blur(&mut self, data: &mut [u8], mask: &[u8], radius: f32) -> Result<(), JsError> {
+        self.try_portrait_blur(data, mask, radius).map_err(|e| JsError::new(&e))
+    }
+
+    /// ワンクリック自動補正を適用
+    /// グレーワールドのホワイトバランス、全チャンネル共通のコントラスト伸長、控えめなバイブランスを
+    /// 1回の統計収集と1回の書き込みでまとめて行う (くすんだWebカメラ映像の補正用)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    pub fn auto_enhance(&mut self, data: &mut [u8]) {
+        console_log!("Applying auto enhance");
+        
+        let pixel_count = data.len() / 4;
+        if pixel_count == 0 {
+            return;
+        }
+        
+        // チャンネルごとの平均と最小・最大値を一度に集計
+        let histograms = channel_histograms(data);
+        let mut gains = [1.0f32; 3];
+        let mut means = [0.0f32; 3];
+        for (c, histogram) in histograms.iter().enumerate() {
+            let sum: u64 = histogram.iter().enumerate().map(|(v, &n)| v as u64 * n as u64).sum();
+            means[c] = sum as f32 / pixel_count as f32;
+        }
+        let target = (means[0] + means[1] + means[2]) / 3.0;
+        for c in 0..3 {
+            if means[c] > 0.0 {
+                gains[c] = target / means[c];
+            }
+        }
+        
+        // ホワイトバランス後の値域を全チャンネル共通で0..255に伸長する
+        let mut low = f32::MAX;
+        let mut high = f32::MIN;
+        for (c, histogram) in histograms.iter().enumerate() {
+            if let (Some(lo), Some(hi)) = (histogram.iter().position(|&n| n > 0), histogram.iter().rposition(|&n| n > 0)) {
+                low = low.min(lo as f32 * gains[c]);
+                high = high.max((hi as f32 * gains[c]).min(255.0));
+            }
+        }
+        let scale = if high > low { 255.0 / (high - low) } else { 1.0 };
+        let low = if high > low { low } else { 0.0 };
+        
+        for pixel in data.chunks_exact_mut(4) {
+            for c in 0..3 {
+                let balanced = (pixel[c] as f32 * gains[c]).min(255.0);
+                pixel[c] = ((balanced - low) * scale).round().clamp(0.0, 255.0) as u8;
+            }
+            self.vibrance_pixel(pixel, AUTO_ENHANCE_VIBRANCE);
+        }
+    }
+
+    /// スポットライト（放射状のグラデーション）エフェクトを適用
+    /// 中心の円形領域を明るくし、その外側を暗くする (中心を移動できる逆ビネット)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `cx`, `cy` - スポットライトの中心座標 (ピクセル)
+    /// * `radius` - 明るくする円の半径 (ピクセル)
+    /// * `falloff` - 円の縁から暗くなりきるまでの幅 (ピクセル、0以下でくっきりした縁)
+    pub fn spotlight(&mut self, data: &mut [u8], cx: f32, cy: f32, radius: f32, falloff: f32) {
+        console_log!("Applying spotlight: center=({}, {}), radius={}, falloff={}", cx, cy, radius, falloff);
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        
+        for y in 0..height {
+            for x in 0..width {
+                let dx = x as f32 - cx;
+                let dy = y as f32 - cy;
+                let outside = (dx * dx + dy * dy).sqrt() - radius;
+                
+                // 円の内側は0、縁から`falloff`離れるにつれて1へ (smoothstep)
+                let t = if falloff > 0.0 {
+                    let t = (outside / falloff).clamp(0.0, 1.0);
+                    t * t * (3.0 - 2.0 * t)
+                } else if outside > 0.0 {
+                    1.0
+                } else {
+                    0.0
+                };
+                let factor = SPOTLIGHT_GAIN + (SPOTLIGHT_AMBIENT - SPOTLIGHT_GAIN) * t;
+                
+                let idx = (y * width + x) * 4;
+                for c in 0..3 {
+                    data[idx + c] = (data[idx + c] as f32 * factor).round().clamp(0.0, 255.0) as u8;
+                }
+                // Alpha値は保持
+            }
+        }
+    }
+
+    /// 現在の画像サイズでフィルタを適用した場合のおおよその演算回数を見積もる
+    /// 実際には適用しないため、縮小してから処理するかどうかの判断に使用できる
+    /// 
+    /// `apply`と同じデフォルトのパラメータ (ブラー半径`DEFAULT_BLUR_RADIUS` = 3.0、
+    /// シャープネス量1.0、hdr_animeはgamma 0.7 / saturation 1.8 / levels 6 / edge 0.5) を前提とする。
+    /// ただしブラーは累積和で計算するため半径によらず、シャープネスは3x3カーネル固定、
+    /// hdr_animeは画素ごとの演算数が一定のため、どのパラメータでも見積もりは変わらない
+    /// 
+    /// # Arguments
+    /// * `filter` - 見積もるフィルタ
+    /// 
+    /// # Returns
+    /// 演算回数の目安 (フィルタ間・サイズ間の相対比較用)
+    pub fn estimate_cost(&self, filter: Filter) -> u64 {
+        self.width as u64 * self.height as u64 * filter_cost_per_pixel(filter)
+    }
+
+    /// 水彩画風のエフェクトを適用
+    /// 中央値フィルタの反復で色面を平らにし、ブラーを混ぜて隣接色をにじませ、
+    /// 色面の境界を暗くして絵の具のたまりを表現する
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `radius` - 平滑化の強さ (中央値フィルタの反復回数、0で変化なし)
+    pub fn watercolor(&mut self, data: &mut [u8], radius: u32) {
+        console_log!("Applying watercolor with radius: {}", radius);
+        
+        if radius == 0 {
+            return;
+        }
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        
+        // 中央値フィルタの反復で細部を落として色面を作る
+        let mut smoothed = data.to_vec();
+        for _ in 0..radius {
+            let temp_data = smoothed.clone();
+            for y in 0..height {
+                for x in 0..width {
+                    let idx = (y * width + x) * 4;
+                    let median = self.median3x3(&temp_data, x, y, width, height);
+                    smoothed[idx..idx + 3].copy_from_slice(&median);
+                }
+            }
+        }
+        
+        // ぼかした色を混ぜて境界をにじませる
+        let mut bled = smoothed.clone();
+        self.gaussian_blur(&mut bled, radius as f32);
+        
+        // 色面の境界 (平滑化後の勾配) を暗くする
+        let gray = self.gray_buffer(&smoothed);
+        let (gradient_x, gradient_y) = self.sobel_gradients(&gray, width, height);
+        
+        for (i, pixel) in data.chunks_exact_mut(4).enumerate() {
+            let magnitude = ((gradient_x[i] * gradient_x[i] + gradient_y[i] * gradient_y[i]) as f32).sqrt();
+            let darken = 1.0 - WATERCOLOR_EDGE_DARKEN * (magnitude / 1020.0).min(1.0);
+            for (c, value) in pixel[..3].iter_mut().enumerate() {
+                let idx = i * 4 + c;
+                let color = smoothed[idx] as f32 * (1.0 - WATERCOLOR_BLEED) + bled[idx] as f32 * WATERCOLOR_BLEED;
+                *value = (color * darken).round().clamp(0.0, 255.0) as u8;
+            }
+            // Alpha値は保持
+        }
+    }
+
+    /// 乗算済みAlphaでガウシアンブラーを適用
+    /// 透明画素の色 (通常は黒) が不透明な領域へにじむのを防ぐため、
+    /// RGBにAlphaを掛けてからRGBAをまとめてぼかし、最後にAlphaで割り戻す
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `radius` - ブラー半径
+    pub fn gaussian_blur_premul(&mut self, data: &mut [u8], radius: f32) {
+        console_log!("Applying premultiplied gaussian blur with radius: {}", radius);
+        
+        if radius <= 0.0 {
+            return;
+        }
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        
+        // 8bitで乗算すると半透明部分の階調が失われるため浮動小数点のままぼかす
+        let mut premultiplied: Vec<f32> = data[..width * height * 4]
+            .chunks_exact(4)
+            .flat_map(|p| {
+                let alpha = p[3] as f32 / 255.0;
+                [p[0] as f32 * alpha, p[1] as f32 * alpha, p[2] as f32 * alpha, p[3] as f32]
+            })
+            .collect();
+        
+        self.box_blur_f32(&mut premultiplied, width, height, 4, radius);
+        
+        for (pixel, rgba) in data.chunks_exact_mut(4).zip(premultiplied.chunks_exact(4)) {
+            let alpha = rgba[3] / 255.0;
+            for c in 0..3 {
+                // 完全に透明な画素は色を持たない
+                let color = if alpha > 0.0 { rgba[c] / alpha } else { 0.0 };
+                pixel[c] = color.round().clamp(0.0, 255.0) as u8;
+            }
+            pixel[3] = rgba[3].round().clamp(0.0, 255.0) as u8;
+        }
+    }
+
+    /// 構図ガイド用のグリッド線を描画
+    /// 画像を`rows`行`cols`列に等分する位置に1pxの線を引く (3, 3で三分割法)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `rows` - 行数 (1以下で水平線なし)
+    /// * `cols` - 列数 (1以下で垂直線なし)
+    /// * `r`, `g`, `b` - 線の色 (Alpha値は保持)
+    pub fn draw_grid(&mut self, data: &mut [u8], rows: u32, cols: u32, r: u8, g: u8, b: u8) {
+        console_log!("Drawing grid: {}x{}", rows, cols);
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let color = [r, g, b];
+        
+        // 分割位置 (両端を除く)
+        let lines = |divisions: u32, length: usize| -> Vec<usize> {
+            let divisions = divisions as usize;
+            (1..divisions).map(|i| i * length / divisions).collect()
+        };
+        let line_rows = lines(rows, height);
+        let line_cols = lines(cols, width);
+        
+        for y in 0..height {
+            let on_row = line_rows.contains(&y);
+            for x in 0..width {
+                if on_row || line_cols.contains(&x) {
+                    let idx = (y * width + x) * 4;
+                    data[idx..idx + 3].copy_from_slice(&color);
+                }
+            }
+        }
+    }
+
+    /// 霞を除去してコントラストと色を回復する (Dark Channel Prior)
+    /// 霞のない画像では近傍のいずれかのチャンネルに暗い画素があるという性質から、
+    /// 近傍のチャンネル最小値 (ダークチャンネル) を霞の濃さとして差し引く
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `amount` - 除去の強さ (0.0で変化なし、1.0で推定した霞をすべて除去)
+    pub fn dehaze(&mut self, data: &mut [u8], amount: f32) {
+        console_log!("Applying dehaze with amount: {}", amount);
+        
+        let amount = amount.clamp(0.0, 1.0);
+        let width = self.width as usize;
+        let height = self.height as usize;
+        if amount == 0.0 || width == 0 || height == 0 {
+            return;
+        }
+        
+        // 画素ごとのチャンネル最小値を近傍の最小値で広げる (水平・垂直の順)
+        let pixel_min: Vec<u8> = data[..width * height * 4]
+            .chunks_exact(4)
+            .map(|p| p[0].min(p[1]).min(p[2]))
+            .collect();
+        let mut horizontal = vec![0u8; width * height];
+        for y in 0..height {
+            for x in 0..width {
+                let start = x.saturating_sub(DEHAZE_PATCH_RADIUS);
+                let end = (x + DEHAZE_PATCH_RADIUS + 1).min(width);
+                horizontal[y * width + x] = pixel_min[y * width + start..y * width + end].iter().copied().min().unwrap();
+            }
+        }
+        let mut dark_channel = vec![0u8; width * height];
+        for y in 0..height {
+            let start = y.saturating_sub(DEHAZE_PATCH_RADIUS);
+            let end = (y + DEHAZE_PATCH_RADIUS + 1).min(height);
+            for x in 0..width {
+                dark_channel[y * width + x] = (start..end).map(|py| horizontal[py * width + x]).min().unwrap();
+            }
+        }
+        
+        // ダークチャンネルが最も明るい画素の色を大気光 (霞の色) とする
+        let haziest = (0..width * height).max_by_key(|&i| dark_channel[i]).unwrap();
+        let atmosphere: Vec<f32> = data[haziest * 4..haziest * 4 + 3]
+            .iter()
+            .map(|&c| (c as f32).max(1.0))
+            .collect();
+        let atmosphere_min = atmosphere.iter().copied().fold(f32::MAX, f32::min);
+        
+        for (pixel, &dark) in data.chunks_exact_mut(4).zip(dark_channel.iter()) {
+            let transmission = (1.0 - amount * dark as f32 / atmosphere_min).max(DEHAZE_MIN_TRANSMISSION);
+            for c in 0..3 {
+                let recovered = (pixel[c] as f32 - atmosphere[c]) / transmission + atmosphere[c];
+                pixel[c] = recovered.round().clamp(0.0, 255.0) as u8;
+            }
+            // Alpha値は保持
+        }
+    }
+
+    /// 輝度が最大・最小の画素の座標を求める (スポット測光用)
+    /// 同じ輝度の画素が複数ある場合は走査順で最初のものを返す
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ
+    /// 
+    /// # Returns
+    /// `[最も明るい画素のx, y, 最も暗い画素のx, y]` (画像が空の場合は空)
+    pub fn find_extremes(&self, data: &[u8]) -> Vec<u32> {
+        let width = self.width as usize;
+        let pixel_count = (width * self.height as usize).min(data.len() / 4);
+        if pixel_count == 0 {
+            return Vec::new();
+        }
+        
+        let mut brightest = (0, f32::MIN);
+        let mut darkest = (0, f32::MAX);
+        for (i, pixel) in data[..pixel_count * 4].chunks_exact(4).enumerate() {
+            let luma = self.luma(pixel[0] as f32, pixel[1] as f32, pixel[2] as f32);
+            if luma > brightest.1 {
+                brightest = (i, luma);
+            }
+            if luma < darkest.1 {
+                darkest = (i, luma);
+            }
+        }
+        
+        let position = |i: usize| [(i % width) as u32, (i / width) as u32];
+        [position(brightest.0), position(darkest.0)].concat()
+    }
+
+    /// 特定の色相範囲の画素だけ色相を回転させる
+    /// `target_hue ± range` の範囲内の有彩色のみ変更し、それ以外の画素はそのまま残す
+    /// (赤い物体だけを緑にする、など)
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `target_hue` - 変更する色相の中心 (0.0..360.0)
+    /// * `range` - 変更する色相の幅 (度)
+    /// * `shift` - 色相の回転量 (度)
+    pub fn shift_hue_range(&mut self, data: &mut [u8], target_hue: f32, range: f32, shift: f32) {
+        console_log!("Applying hue range shift: hue={}, range={}, shift={}", target_hue, range, shift);
+        
+        for_each_pixel(data, |pixel| {
+            let (hue, saturation, value) = rgb_to_hsv(pixel[0], pixel[1], pixel[2]);
+            
+            // 無彩色または範囲外の色相は変更しない
+            if saturation == 0.0 || hue_distance(hue, target_hue) > range {
+                return;
+            }
+            pixel[..3].copy_from_slice(&hsv_to_rgb(hue + shift, saturation, value));
+            // Alpha値は保持
+        });
+    }
+
+    /// 標準偏差を指定して本来のガウス分布の重みでブラーを適用
+    /// `gaussian_blur`のボックス近似より重いが、ぼけ方が滑らかになる
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `sigma` - ガウス分布の標準偏差 (0以下で変化なし)
+    pub fn gaussian_blur_sigma(&mut self, data: &mut [u8], sigma: f32) {
+        console_log!("Applying gaussian blur with sigma: {}", sigma);
+        
+        if sigma <= 0.0 {
+            return;
+        }
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let kernel = build_gaussian_kernel(sigma);
+        self.convolve_1d(data, width, height, &kernel, true);
+        self.convolve_1d(data, width, height, &kernel, false);
+    }
+
+    /// 任意の正規化済み1Dカーネルでブラーを適用
+    /// 水平方向、垂直方向の順に適用する (境界はクランプ)。
+    /// `convolve_separable`と異なり重みの合計が1であることを検証するため、
+    /// 画像全体の明るさは変わらない
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `kernel_1d` - 奇数長で合計が1の1Dカーネル
+    /// 
+    /// # Errors
+    /// カーネル長が奇数でない場合、または重みの合計が1でない場合
+    pub fn blur_with_kernel(&mut self, data: &mut [u8], kernel_1d: &[f32]) -> Result<(), JsError> {
+        self.try_blur_with_kernel(data, kernel_1d).map_err(|e| JsError::new(&e))
+    }
+
+    /// 2つの画像をマスクで画素ごとに合成
+    /// 同じフレームに異なる処理をした結果を組み合わせる場合に使用
+    /// (マスクが暗い部分はブラー、明るい部分はシャープ、など)
+    /// 
+    /// # Arguments
+    /// * `a` - マスク値0で採用するRGBA画像データ
+    /// * `b` - マスク値255で採用するRGBA画像データ
+    /// * `mask` - 画素ごとの合成比率 (width * height、0で`a`、255で`b`)
+    /// 
+    /// # Returns
+    /// 合成したRGBA画像データ (Alphaも同じ比率で合成)
+    /// 
+    /// # Errors
+    /// `a`と`b`の長さが`width * height * 4`でない場合、または`mask`の長さが`width * height`でない場合
+    pub fn blend_with_mask(&self, a: &[u8], b: &[u8], mask: &[u8]) -> Result<Vec<u8>, JsError> {
+        self.try_blend_with_mask(a, b, mask).map_err(|e| JsError::new(&e))
+    }
+
+    /// 色相・彩度・明度をそれぞれ独立に階調化
+    /// RGBごとの`posterize`と異なり、色味を保ったまま明るさだけ、
+    /// あるいは色相だけを段階化するといった表現ができる
+    /// 
+    /// # Arguments
+    /// * `data` - RGBA画像データ (mutable)
+    /// * `h_levels` - 色相の段階数 (色相環をこの数に等分)
+    /// * `s_levels` - 彩度の段階数 (0.0と1.0を含む)
+    /// * `v_levels` - 明度の段階数 (0.0と1.0を含む)
+    /// 
+    /// いずれも2未満の場合はその成分を変更しない
+    pub fn posterize_hsv(&mut self, data: &mut [u8], h_levels: u32, s_levels: u32, v_levels: u32) {
+        console_log!("Applying HSV posterize: h={}, s={}, v={}", h_levels, s_levels, v_levels);
+        
+        let quantize = |value: f32, levels: u32| {
+            if levels < 2 {
+                return value;
+            }
+            let steps = (levels - 1) as f32;
+            (value * steps).round() / steps
+        };
+        let quantize_hue = |hue: f32| {
+            if h_levels < 2 {
+                return hue;
+            }
+            let sector = 360.0 / h_levels as f32;
+            (hue / sector).round() * sector
+        };
+        
+        for_each_pixel(data, |pixel| {
+            let (hue, saturation, value) = rgb_to_hsv(pixel[0], pixel[1], pixel[2]);
+            let rgb = hsv_to_rgb(quantize_hue(hue), quantize(saturation, s_levels), quantize(value, v_levels));
+            pixel[..3].copy_from_slice(&rgb);
+            // Alpha値は保持
+        });
+    }
+}
+
+impl ImageProcessor {
+    /// Sobel強度をグレースケールで書き込む
+    /// `noise_floor`未満の強度は0として扱う
+    fn apply_sobel_magnitude(&self, data: &mut [u8], noise_floor: u8) {
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let gray = self.gray_buffer(data);
+        let (gradient_x, gradient_y) = self.sobel_gradients(&gray, width, height);
+        
+        // Sobelオペレータを使用したエッジ検出
+        for_each_row(&mut data[..width * height * 4], width * 4, |y, row| {
+            if y == 0 || y + 1 >= height {
+                return;
+            }
+            for x in 1..width - 1 {
+                let idx = x * 4;
+                
+                // Sobelフィルタ適用
+                let gx = gradient_x[y * width + x];
+                let gy = gradient_y[y * width + x];
+                
+                let mut magnitude = ((gx * gx + gy * gy) as f32).sqrt() as u8;
+                if magnitude < noise_floor {
+                    magnitude = 0;
+                }
+                
+                row[idx] = magnitude;     // R
+                row[idx + 1] = magnitude; // G
+                row[idx + 2] = magnitude; // B
+                // Alpha値は保持
+            }
+        });
+    }
+
+    /// 水平方向のブラー処理
+    fn blur_horizontal(&self, data: &mut [u8], width: usize, height: usize, radius: f32) {
+        self.blur_horizontal_by_row(data, width, height, 3, |_| radius);
+    }
+    
+    /// 垂直方向のブラー処理
+    fn blur_vertical(&self, data: &mut [u8], width: usize, height: usize, radius: f32) {
+        self.blur_vertical_by_row(data, width, height, 3, |_| radius);
+    }
+
+    /// 行ごとに半径を変えて水平方向のブラー処理
+    /// `channels`が4の場合はAlphaチャンネルも平均化する
+    fn blur_horizontal_by_row<F: Fn(usize) -> f32 + Sync>(&self, data: &mut [u8], width: usize, height: usize, channels: usize, radius_at: F) {
+        let temp_data = data.to_vec();
+        
+        for_each_row(&mut data[..width * height * 4], width * 4, |y, row| {
+            self.blur_row_horizontal(&temp_data, row, y, width, channels, radius_at(y));
+        });
+    }
+    
+    /// 出力行ごとに半径を変えて垂直方向のブラー処理
+    /// `channels`が4の場合はAlphaチャンネルも平均化する
+    fn blur_vertical_by_row<F: Fn(usize) -> f32 + Sync>(&self, data: &mut [u8], width: usize, height: usize, channels: usize, radius_at: F) {
+        let column_sums = column_prefix_sums(data, width, height, channels);
+        
+        for_each_row(&mut data[..width * height * 4], width * 4, |y, row| {
+            self.blur_row_vertical(&column_sums, row, y, width, height, channels, radius_at(y));
+        });
+    }
+
+    /// 1行分の水平方向ブラー (`src`は処理前の画像全体)
+    /// 行の累積和から区間の合計を求めるため、計算量は半径によらない
+    fn blur_row_horizontal(&self, src: &[u8], row: &mut [u8], y: usize, width: usize, channels: usize, radius: f32) {
+        // 画像幅を超える半径は結果が変わらないため幅で打ち切る
+        let radius = radius.min(width as f32);
+        let kernel_size = (radius * 2.0) as usize + 1;
+        let offset = radius as i32;
+        
+        // prefix[x * channels + c] は x より左の画素の合計
+        let mut prefix = vec![0u32; (width + 1) * channels];
+        for x in 0..width {
+            let idx = (y * width + x) * 4;
+            for c in 0..channels {
+                prefix[(x + 1) * channels + c] = prefix[x * channels + c] + src[idx + c] as u32;
+            }
+        }
+        
+        for x in 0..width {
+            // 画像内に収まるサンプル範囲のみを集計
+            let start = (x as i32 - offset).max(0);
+            let end = (x as i32 - offset + kernel_size as i32).min(width as i32);
+            
+            // 有効なサンプルがない場合は元の値を保持
+            if start >= end {
+                continue;
+            }
+            
+            let (start, end) = (start as usize, end as usize);
+            let count = (end - start) as f32;
+            for c in 0..channels {
+                let sum = prefix[end * channels + c] - prefix[start * channels + c];
+                row[x * 4 + c] = (sum as f32 / count) as u8;
+            }
+        }
+    }
+
+    /// 1行分の垂直方向ブラー (`column_sums`は`column_prefix_sums`の結果)
+    /// 列の累積和から区間の合計を求めるため、計算量は半径によらない
+    #[allow(clippy::too_many_arguments)]
+    fn blur_row_vertical(&self, column_sums: &[u32], row: &mut [u8], y: usize, width: usize, height: usize, channels: usize, radius: f32) {
+        // 画像の高さを超える半径は結果が変わらないため高さで打ち切る
+        let radius = radius.min(height as f32);
+        let kernel_size = (radius * 2.0) as usize + 1;
+        let offset = radius as i32;
+        
+        // サンプルする行の範囲は行内で共通
+        let start = (y as i32 - offset).max(0);
+        let end = (y as i32 - offset + kernel_size as i32).min(height as i32);
+        
+        // 有効なサンプルがない場合は元の値を保持
+        if start >= end {
+            return;
+        }
+        let (start, end) = (start as usize, end as usize);
+        let count = (end - start) as f32;
+        let line = width * channels;
+        
+        for x in 0..width {
+            for c in 0..channels {
+                let i = x * channels + c;
+                let sum = column_sums[end * line + i] - column_sums[start * line + i];
+                row[x * 4 + c] = (sum as f32 / count) as u8;
+            }
+        }
+    }
+    
+    /// グレースケール値を取得
+    fn get_gray_value(&self, data: &[u8], x: usize, y: usize, width: usize) -> u8 {
+        let idx = (y * width + x) * 4;
+        self.luma(data[idx] as f32, data[idx + 1] as f32, data[idx + 2] as f32) as u8
+    }
+
+    /// RGB値から輝度を計算 (`luma_weights`の重み係数を使用)
+    fn luma(&self, r: f32, g: f32, b: f32) -> f32 {
+        match self.luma_weights {
+            LumaWeights::Bt601 => r * 0.299 + g * 0.587 + b * 0.114,
+            LumaWeights::Bt709 => r * 0.2126 + g * 0.7152 + b * 0.0722,
+        }
+    }
+    
+    /// 画像全体のグレースケール値を計算
+    fn gray_buffer(&self, data: &[u8]) -> Vec<u8> {
+        data.chunks_exact(4)
+            .map(|p| self.luma(p[0] as f32, p[1] as f32, p[2] as f32) as u8)
+            .collect()
+    }
+
+    /// グレースケール画像のSobel勾配 (X方向, Y方向) を計算
+    /// Sobelカーネルは [1, 2, 1] と [-1, 0, 1] に分離できるため、
+    /// 水平方向と垂直方向の1Dパスで計算する (外周の画素は0)
+    fn sobel_gradients(&self, gray: &[u8], width: usize, height: usize) -> (Vec<i32>, Vec<i32>) {
+        let mut gradient_x = vec![0i32; width * height];
+        let mut gradient_y = vec![0i32; width * height];
+        if width < 3 || height < 3 {
+            return (gradient_x, gradient_y);
+        }
+        
+        // 水平方向のパス: 差分 [-1, 0, 1] と平滑化 [1, 2, 1]
+        let mut diff = vec![0i32; width * height];
+        let mut smooth = vec![0i32; width * height];
+        for y in 0..height {
+            for x in 1..width - 1 {
+                let i = y * width + x;
+                let left = gray[i - 1] as i32;
+                let center = gray[i] as i32;
+                let right = gray[i + 1] as i32;
+                diff[i] = right - left;
+                smooth[i] = left + 2 * center + right;
+            }
+        }
+        
+        // 垂直方向のパス: X勾配は差分を平滑化、Y勾配は平滑化を差分
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                let i = y * width + x;
+                gradient_x[i] = diff[i - width] + 2 * diff[i] + diff[i + width];
+                gradient_y[i] = smooth[i + width] - smooth[i - width];
+            }
+        }
+        
+        (gradient_x, gradient_y)
+    }
+
+    /// HDR強調処理を適用
+    fn apply_hdr_enhancement(&self, data: &mut [u8], gamma: f32, saturation_boost: f32) {
+        for_each_pixel(data, |pixel| {
+            let r = pixel[0] as f32 / 255.0;
+            let g = pixel[1] as f32 / 255.0;
+            let b = pixel[2] as f32 / 255.0;
+
+            // ガンマ補正（コントラスト強化）
+            let enhanced_r = r.powf(gamma);
+            let enhanced_g = g.powf(gamma);
+            let enhanced_b = b.powf(gamma);
+
+            // 彩度向上
+            let gray = self.luma(enhanced_r, enhanced_g, enhanced_b);
+            
+            let final_r = gray + (enhanced_r - gray) * saturation_boost;
+            let final_g = gray + (enhanced_g - gray) * saturation_boost;
+            let final_b = gray + (enhanced_b - gray) * saturation_boost;
+
+            pixel[0] = (final_r * 255.0).clamp(0.0, 255.0) as u8;
+            pixel[1] = (final_g * 255.0).clamp(0.0, 255.0) as u8;
+            pixel[2] = (final_b * 255.0).clamp(0.0, 255.0) as u8;
+        });
+    }
+
+    /// アニメ調色階調化処理を適用
+    fn apply_anime_posterization(&self, data: &mut [u8], levels: u32) {
+        let step = 255.0 / (levels - 1) as f32;
+
+        for_each_pixel(data, |pixel| {
+            for value in &mut pixel[..3] {
+                let level = (*value as f32 / step).round();
+                *value = (level * step).min(255.0) as u8;
+            }
+        });
+    }
+
+    /// エッジ強調処理を適用
+    /// `outline.thickness`が1以上の場合はエッジを明るくする代わりに、
+    /// 閾値を超えるエッジを輪郭線として描画する
+    fn apply_edge_enhancement(&self, data: &mut [u8], width: usize, height: usize, strength: f32, outline: EdgeOutline) {
+        let temp_data = data.to_vec();
+        let gray = self.gray_buffer(&temp_data);
+        let (gradient_x, gradient_y) = self.sobel_gradients(&gray, width, height);
+        
+        if outline.thickness > 0 {
+            draw_edge_outline(data, &gradient_x, &gradient_y, width, height, outline);
+            return;
+        }
+        
+        for_each_row(&mut data[..width * height * 4], width * 4, |y, row| {
+            if y == 0 || y + 1 >= height {
+                return;
+            }
+            for x in 1..width - 1 {
+                let idx = (y * width + x) * 4;
+                
+                // エッジ検出
+                let gx = gradient_x[y * width + x];
+                let gy = gradient_y[y * width + x];
+                let edge_strength = ((gx * gx + gy * gy) as f32).sqrt() / 255.0;
+                
+                // エッジ強調を元の色に適用
+                let enhancement = 1.0 + edge_strength * strength;
+                
+                for c in 0..3 {
+                    let original = temp_data[idx + c] as f32;
+                    let enhanced = original * enhancement;
+                    row[x * 4 + c] = enhanced.min(255.0) as u8;
+                }
+            }
+        });
+    }
+
+    /// ポスタリゼーションの本体 (引数を検証してから適用)
+    fn try_posterize(&mut self, data: &mut [u8], levels: u32) -> Result<(), String> {
+        validate_levels(levels)?;
+        
+        console_log!("Applying posterize with levels: {}", levels);
+        self.apply_anime_posterization(data, levels);
+        Ok(())
+    }
+
+    /// カラーマトリクス変換の本体
+    fn try_color_matrix(&mut self, data: &mut [u8], matrix: &[f32]) -> Result<(), String> {
+        // 変換するチャンネル数 (3: RGB, 4: RGBA)
+        let channels = match matrix.len() {
+            12 => 3,
+            20 => 4,
+            len => return Err(format!("matrix must have 12 (3x4) or 20 (4x5) elements, got {}", len)),
+        };
+        let row_len = channels + 1;
+        
+        console_log!("Applying color matrix ({}x{})", channels, row_len);
+        
+        for i in (0..data.len()).step_by(4) {
+            let src = [data[i] as f32, data[i + 1] as f32, data[i + 2] as f32, data[i + 3] as f32];
+            
+            for c in 0..channels {
+                let row = &matrix[c * row_len..(c + 1) * row_len];
+                let mut value = row[channels]; // オフセット
+                for k in 0..channels {
+                    value += src[k] * row[k];
+                }
+                data[i + c] = value.clamp(0.0, 255.0) as u8;
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// 分離可能畳み込みの本体
+    fn try_convolve_separable(&mut self, data: &mut [u8], kernel_1d: &[f32]) -> Result<(), String> {
+        if kernel_1d.len().is_multiple_of(2) {
+            return Err(format!("kernel length must be odd, got {}", kernel_1d.len()));
+        }
+        
+        console_log!("Applying separable convolution with kernel size: {}", kernel_1d.len());
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        self.convolve_1d(data, width, height, kernel_1d, true);
+        self.convolve_1d(data, width, height, kernel_1d, false);
+        Ok(())
+    }
+
+    /// 1Dカーネルによる畳み込み (RGBのみ、境界はクランプ)
+    fn convolve_1d(&self, data: &mut [u8], width: usize, height: usize, kernel: &[f32], horizontal: bool) {
+        let radius = (kernel.len() / 2) as i32;
+        let temp_data = data.to_vec();
+        
+        for_each_row(&mut data[..width * height * 4], width * 4, |y, row| {
+            for x in 0..width {
+                let mut sum = [0.0f32; 3];
+                
+                for (k, weight) in kernel.iter().enumerate() {
+                    let offset = k as i32 - radius;
+                    let (px, py) = if horizontal {
+                        ((x as i32 + offset).clamp(0, width as i32 - 1) as usize, y)
+                    } else {
+                        (x, (y as i32 + offset).clamp(0, height as i32 - 1) as usize)
+                    };
+                    let idx = (py * width + px) * 4;
+                    for c in 0..3 {
+                        sum[c] += temp_data[idx + c] as f32 * weight;
+                    }
+                }
+                
+                for c in 0..3 {
+                    row[x * 4 + c] = sum[c].round().clamp(0.0, 255.0) as u8;
+                }
+            }
+        });
+    }
+
+    /// トーンカーブの本体
+    fn try_tone_curve(&mut self, data: &mut [u8], points: &[f32]) -> Result<(), String> {
+        let lut = build_tone_lut(points)?;
+        
+        console_log!("Applying tone curve with {} points", points.len() / 2);
+        
+        for i in (0..data.len()).step_by(4) {
+            data[i] = lut[data[i] as usize];
+            data[i + 1] = lut[data[i + 1] as usize];
+            data[i + 2] = lut[data[i + 2] as usize];
+            // Alpha値は保持
+        }
+        
+        Ok(())
+    }
+
+    /// 指定画素のバイラテラルフィルタ結果を計算
+    /// 近傍の画素を色差に応じたガウス重みで平均する (RGBのみ)
+    #[allow(clippy::too_many_arguments)]
+    fn bilateral_at(&self, data: &[u8], x: usize, y: usize, width: usize, height: usize, radius: i32, sigma_color: f32) -> [f32; 3] {
+        let idx = (y * width + x) * 4;
+        let center = [data[idx] as f32, data[idx + 1] as f32, data[idx + 2] as f32];
+        let denom = 2.0 * sigma_color * sigma_color;
+        
+        let mut sum = [0.0f32; 3];
+        let mut weight_sum = 0.0;
+        for ky in -radius..=radius {
+            for kx in -radius..=radius {
+                let px = (x as i32 + kx).clamp(0, width as i32 - 1) as usize;
+                let py = (y as i32 + ky).clamp(0, height as i32 - 1) as usize;
+                let pidx = (py * width + px) * 4;
+                
+                let mut diff_sq = 0.0;
+                for c in 0..3 {
+                    let d = data[pidx + c] as f32 - center[c];
+                    diff_sq += d * d;
+                }
+                let weight = (-diff_sq / denom).exp();
+                
+                for c in 0..3 {
+                    sum[c] += data[pidx + c] as f32 * weight;
+                }
+                weight_sum += weight;
+            }
+        }
+        
+        [sum[0] / weight_sum, sum[1] / weight_sum, sum[2] / weight_sum]
+    }
+
+    /// チャンネル抽出の本体
+    fn try_extract_channel(&self, data: &[u8], channel: u32) -> Result<Vec<u8>, String> {
+        if channel >= 4 {
+            return Err(format!("channel must be in 0..4, got {}", channel));
+        }
+        
+        let c = channel as usize;
+        Ok(data.chunks_exact(4)
+            .flat_map(|pixel| [pixel[c], pixel[c], pixel[c], 255])
+            .collect())
+    }
+
+    /// グラデーションマップの本体
+    fn try_gradient_map(&mut self, data: &mut [u8], stops: &[u8]) -> Result<(), String> {
+        let lut = build_gradient_lut(stops)?;
+        
+        console_log!("Applying gradient map with {} stops", stops.len() / 3);
+        
+        self.apply_gradient_lut(data, &lut);
+        Ok(())
+    }
+
+    /// 輝度をインデックスとしてグラデーションLUTを適用
+    fn apply_gradient_lut(&self, data: &mut [u8], lut: &[[u8; 3]; 256]) {
+        for i in (0..data.len()).step_by(4) {
+            let luma = self.luma(data[i] as f32, data[i + 1] as f32, data[i + 2] as f32) as usize;
+            let color = lut[luma.min(255)];
+            data[i] = color[0];
+            data[i + 1] = color[1];
+            data[i + 2] = color[2];
+            // Alpha値は保持
+        }
+    }
+
+    /// 3x3近傍のチャンネルごとの中央値を計算 (境界はクランプ)
+    fn median3x3(&self, data: &[u8], x: usize, y: usize, width: usize, height: usize) -> [u8; 3] {
+        let mut median = [0u8; 3];
+        for (c, value) in median.iter_mut().enumerate() {
+            let mut window = [0u8; 9];
+            for ky in 0..3 {
+                for kx in 0..3 {
+                    let px = (x as i32 + kx as i32 - 1).clamp(0, width as i32 - 1) as usize;
+                    let py = (y as i32 + ky as i32 - 1).clamp(0, height as i32 - 1) as usize;
+                    window[ky * 3 + kx] = data[(py * width + px) * 4 + c];
+                }
+            }
+            window.sort_unstable();
+            *value = window[4];
+        }
+        median
+    }
+
+    /// バイリニア補間で画素をサンプリング (座標は画像内にクランプ)
+    fn sample_bilinear<T: Copy + Into<f32>>(&self, data: &[T], fx: f32, fy: f32, width: usize, height: usize) -> [f32; 4] {
+        let fx = fx.clamp(0.0, width as f32 - 1.0);
+        let fy = fy.clamp(0.0, height as f32 - 1.0);
+        let x0 = fx.floor() as usize;
+        let y0 = fy.floor() as usize;
+        let x1 = (x0 + 1).min(width - 1);
+        let y1 = (y0 + 1).min(height - 1);
+        let tx = fx - x0 as f32;
+        let ty = fy - y0 as f32;
+        
+        let mut color = [0.0f32; 4];
+        for (c, value) in color.iter_mut().enumerate() {
+            let p00: f32 = data[(y0 * width + x0) * 4 + c].into();
+            let p10: f32 = data[(y0 * width + x1) * 4 + c].into();
+            let p01: f32 = data[(y1 * width + x0) * 4 + c].into();
+            let p11: f32 = data[(y1 * width + x1) * 4 + c].into();
+            let top = p00 + (p10 - p00) * tx;
+            let bottom = p01 + (p11 - p01) * tx;
+            *value = top + (bottom - top) * ty;
+        }
+        color
+    }
+
+    /// 同じ設定で別サイズのプロセッサを作成 (タイル処理用)
+    /// 前フレームは引き継がない
+    fn sub_processor(&self, width: u32, height: u32) -> ImageProcessor {
+        ImageProcessor { width, height, previous_frame: Vec::new(), luma_weights: self.luma_weights }
+    }
+
+    /// 画素ごとの変換を適用 (map_pixelsの本体)
+    /// 変換がエラーを返した時点で処理を中断する
+    fn try_map_pixels_with<E, F: FnMut([u8; 4]) -> Result<[u8; 4], E>>(&mut self, data: &mut [u8], mut f: F) -> Result<(), E> {
+        for chunk in data.chunks_exact_mut(4) {
+            let pixel = [chunk[0], chunk[1], chunk[2], chunk[3]];
+            chunk.copy_from_slice(&f(pixel)?);
+        }
+        Ok(())
+    }
+
+    /// オーバーレイ合成の本体
+    fn try_overlay_image(&mut self, base: &mut [u8], overlay: &[u8], ow: u32, oh: u32, x: u32, y: u32) -> Result<(), String> {
+        let ow = ow as usize;
+        let oh = oh as usize;
+        if overlay.len() != ow * oh * 4 {
+            return Err(format!("overlay length must be {} ({}x{}x4), got {}", ow * oh * 4, ow, oh, overlay.len()));
+        }
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let (x, y) = (x as usize, y as usize);
+        
+        // 画像内に収まる範囲のみ合成
+        for oy in 0..oh.min(height.saturating_sub(y)) {
+            for ox in 0..ow.min(width.saturating_sub(x)) {
+                let src = (oy * ow + ox) * 4;
+                let dst = ((y + oy) * width + (x + ox)) * 4;
+                let alpha = overlay[src + 3] as f32 / 255.0;
+                
+                for c in 0..3 {
+                    let blended = overlay[src + c] as f32 * alpha + base[dst + c] as f32 * (1.0 - alpha);
+                    base[dst + c] = blended.round() as u8;
+                }
+                let base_alpha = base[dst + 3] as f32 / 255.0;
+                base[dst + 3] = ((alpha + base_alpha * (1.0 - alpha)) * 255.0).round() as u8;
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// MSE計算の本体
+    fn try_mse(&self, a: &[u8], b: &[u8]) -> Result<f64, String> {
+        validate_same_length(a, b)?;
+        if a.is_empty() {
+            return Ok(0.0);
+        }
+        
+        let sum: f64 = a.chunks_exact(4)
+            .zip(b.chunks_exact(4))
+            .flat_map(|(pa, pb)| (0..3).map(move |c| {
+                let d = pa[c] as f64 - pb[c] as f64;
+                d * d
+            }))
+            .sum();
+        Ok(sum / (a.len() / 4 * 3) as f64)
+    }
+
+    /// SSIM計算の本体
+    fn try_ssim(&self, a: &[u8], b: &[u8]) -> Result<f64, String> {
+        validate_same_length(a, b)?;
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        // 8bit画像の安定化定数
+        let c1 = (0.01 * 255.0f64).powi(2);
+        let c2 = (0.03 * 255.0f64).powi(2);
+        
+        let mut total = 0.0;
+        let mut blocks = 0;
+        for by in (0..height).step_by(SSIM_BLOCK) {
+            for bx in (0..width).step_by(SSIM_BLOCK) {
+                let mut luma_a = Vec::with_capacity(SSIM_BLOCK * SSIM_BLOCK);
+                let mut luma_b = Vec::with_capacity(SSIM_BLOCK * SSIM_BLOCK);
+                for y in by..(by + SSIM_BLOCK).min(height) {
+                    for x in bx..(bx + SSIM_BLOCK).min(width) {
+                        luma_a.push(self.get_gray_value(a, x, y, width) as f64);
+                        luma_b.push(self.get_gray_value(b, x, y, width) as f64);
+                    }
+                }
+                
+                let n = luma_a.len() as f64;
+                let mean_a = luma_a.iter().sum::<f64>() / n;
+                let mean_b = luma_b.iter().sum::<f64>() / n;
+                let mut var_a = 0.0;
+                let mut var_b = 0.0;
+                let mut covariance = 0.0;
+                for (va, vb) in luma_a.iter().zip(luma_b.iter()) {
+                    var_a += (va - mean_a).powi(2);
+                    var_b += (vb - mean_b).powi(2);
+                    covariance += (va - mean_a) * (vb - mean_b);
+                }
+                var_a /= n;
+                var_b /= n;
+                covariance /= n;
+                
+                total += ((2.0 * mean_a * mean_b + c1) * (2.0 * covariance + c2))
+                    / ((mean_a * mean_a + mean_b * mean_b + c1) * (var_a + var_b + c2));
+                blocks += 1;
+            }
+        }
+        
+        Ok(if blocks == 0 { 1.0 } else { total / blocks as f64 })
+    }
+
+    /// 組織的ディザリングの本体
+    fn try_dither_ordered(&mut self, data: &mut [u8], levels: u32, matrix_size: u32) -> Result<(), String> {
+        validate_dither(levels, matrix_size)?;
+        self.apply_ordered_dither(data, levels, matrix_size);
+        Ok(())
+    }
+
+    /// 検証済みのパラメータで組織的ディザリングを適用
+    fn apply_ordered_dither(&self, data: &mut [u8], levels: u32, matrix_size: u32) {
+        console_log!("Applying ordered dither: levels={}, matrix={}", levels, matrix_size);
+        
+        let n = matrix_size as usize;
+        let matrix = bayer_matrix(n);
+        let width = self.width as usize;
+        let step = 255.0 / (levels - 1) as f32;
+        let max_level = (levels - 1) as f32;
+        
+        for (i, pixel) in data.chunks_exact_mut(4).enumerate() {
+            let (x, y) = (i % width, i / width);
+            // 0.0..1.0 の閾値オフセット
+            let threshold = (matrix[(y % n) * n + (x % n)] as f32 + 0.5) / (n * n) as f32;
+            
+            for value in pixel[..3].iter_mut() {
+                let level = (*value as f32 / step + threshold).floor().min(max_level);
+                *value = (level * step).round() as u8;
+            }
+            // Alpha値は保持
+        }
+    }
+
+    /// 回転の本体
+    fn try_rotate(&self, data: &[u8], degrees: f32, bg: &[u8]) -> Result<Vec<u8>, String> {
+        if bg.len() != 4 {
+            return Err(format!("bg must be [r, g, b, a], got {} values", bg.len()));
+        }
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let cx = (width as f32 - 1.0) / 2.0;
+        let cy = (height as f32 - 1.0) / 2.0;
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        
+        let mut output = vec![0u8; width * height * 4];
+        for y in 0..height {
+            for x in 0..width {
+                // 出力画素から元画像の座標を逆算
+                let dx = x as f32 - cx;
+                let dy = y as f32 - cy;
+                let src_x = cx + dx * cos + dy * sin;
+                let src_y = cy - dx * sin + dy * cos;
+                
+                let idx = (y * width + x) * 4;
+                let inside = src_x > -0.5 && src_x < width as f32 - 0.5
+                    && src_y > -0.5 && src_y < height as f32 - 0.5;
+                if inside {
+                    let color = self.sample_bilinear(data, src_x, src_y, width, height);
+                    for c in 0..4 {
+                        output[idx + c] = color[c].round().clamp(0.0, 255.0) as u8;
+                    }
+                } else {
+                    output[idx..idx + 4].copy_from_slice(bg);
+                }
+            }
+        }
+        
+        Ok(output)
+    }
+
+    /// パレット量子化の本体
+    fn try_quantize_to_palette(&mut self, data: &mut [u8], palette: &[u8]) -> Result<(), String> {
+        if palette.is_empty() || !palette.len().is_multiple_of(3) {
+            return Err(format!(
+                "palette length must be a non-zero multiple of 3, got {}",
+                palette.len()
+            ));
+        }
+        
+        console_log!("Applying palette quantization with {} colors", palette.len() / 3);
+        
+        for pixel in data.chunks_exact_mut(4) {
+            let mut best = &palette[0..3];
+            let mut best_distance = i32::MAX;
+            for entry in palette.chunks_exact(3) {
+                let dr = pixel[0] as i32 - entry[0] as i32;
+                let dg = pixel[1] as i32 - entry[1] as i32;
+                let db = pixel[2] as i32 - entry[2] as i32;
+                let d = dr * dr + dg * dg + db * db;
+                if d < best_distance {
+                    best_distance = d;
+                    best = entry;
+                }
+            }
+            pixel[..3].copy_from_slice(best);
+            // Alpha値は保持
+        }
+        
+        Ok(())
+    }
+
+    /// マスク付きブラーの本体
+    /// 整数半径ごとにぼかした画像を用意し、画素ごとに隣接する2段階を線形補間する
+    fn try_blur_with_mask(&mut self, data: &mut [u8], mask: &[u8], max_radius: f32) -> Result<(), String> {
+        let pixel_count = (self.width * self.height) as usize;
+        if data.len() != pixel_count * 4 {
+            return Err(format!(
+                "data length must be {} (width * height * 4), got {}",
+                pixel_count * 4,
+                data.len()
+            ));
+        }
+        if mask.len() != pixel_count {
+            return Err(format!(
+                "mask length must be {} (width * height), got {}",
+                pixel_count,
+                mask.len()
+            ));
+        }
+        
+        console_log!("Applying mask blur with max radius: {}", max_radius);
+        
+        // 画像サイズを超える半径は結果が変わらないため打ち切る
+        let max_radius = max_radius
+            .min(MAX_MASK_BLUR_RADIUS)
+            .min(self.width.max(self.height) as f32);
+        if max_radius <= 0.0 {
+            return Ok(());
+        }
+        
+        // 段階0は元画像、段階nは半径nのブラー
+        // 隣り合う2段階だけを保持し、半径がその間にある画素を補間する
+        let steps = max_radius.ceil() as usize;
+        let original = data.to_vec();
+        let radii: Vec<f32> = mask.iter().map(|&m| max_radius * m as f32 / 255.0).collect();
+        let mut lower_level = original.clone();
+        
+        for lower in 0..steps {
+            let mut upper_level = original.clone();
+            self.gaussian_blur(&mut upper_level, (lower + 1) as f32);
+            
+            for (i, &radius) in radii.iter().enumerate() {
+                // 最後の段階は上端 (max_radius) を含む
+                let in_range = radius.floor() as usize == lower || (lower + 1 == steps && radius >= steps as f32);
+                if !in_range {
+                    continue;
+                }
+                let t = (radius - lower as f32).min(1.0);
+                
+                let idx = i * 4;
+                for c in 0..3 {
+                    let a = lower_level[idx + c] as f32;
+                    let b = upper_level[idx + c] as f32;
+                    data[idx + c] = (a + (b - a) * t).round() as u8;
+                }
+            }
+            
+            lower_level = upper_level;
+        }
+        
+        Ok(())
+    }
+
+    /// PNGエンコードの本体
+    #[cfg(feature = "image")]
+    fn try_encode_png(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        use image::ImageEncoder;
+        
+        let expected = (self.width * self.height * 4) as usize;
+        if data.len() != expected {
+            return Err(format!(
+                "data length must be {} (width * height * 4), got {}",
+                expected,
+                data.len()
+            ));
+        }
+        
+        let mut png = Vec::new();
+        image::codecs::png::PngEncoder::new(&mut png)
+            .write_image(data, self.width, self.height, image::ExtendedColorType::Rgba8)
+            .map_err(|e| format!("failed to encode PNG: {}", e))?;
+        Ok(png)
+    }
+
+    /// コミック調エフェクトの本体
+    /// エッジは元画像のグレースケールから求め、画像データ自体のコピーは作らない
+    fn try_comic(&mut self, data: &mut [u8], levels: u32, edge_threshold: u8) -> Result<(), String> {
+        validate_levels(levels)?;
+        self.apply_comic(data, levels, edge_threshold);
+        Ok(())
+    }
+
+    /// 検証済みのパラメータでコミック調エフェクトを適用
+    fn apply_comic(&self, data: &mut [u8], levels: u32, edge_threshold: u8) {
+        console_log!("Applying comic: levels={}, edge_threshold={}", levels, edge_threshold);
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let gray = self.gray_buffer(data);
+        let (gradient_x, gradient_y) = self.sobel_gradients(&gray, width, height);
+        
+        self.apply_anime_posterization(data, levels);
+        
+        // 閾値を超えるエッジを黒い輪郭線にする
+        let threshold = edge_threshold as f32;
+        for (i, (gx, gy)) in gradient_x.iter().zip(&gradient_y).enumerate() {
+            let magnitude = ((gx * gx + gy * gy) as f32).sqrt();
+            if magnitude > threshold {
+                data[i * 4..i * 4 + 3].fill(0);
+            }
+        }
+    }
+
+    /// 対称化の本体
+    fn try_symmetry(&mut self, data: &mut [u8], axis: u32) -> Result<(), String> {
+        let width = self.width as usize;
+        let height = self.height as usize;
+        
+        match axis {
+            0 => {
+                console_log!("Applying horizontal symmetry");
+                for y in 0..height {
+                    for x in 0..width / 2 {
+                        let src = (y * width + x) * 4;
+                        let dst = (y * width + (width - 1 - x)) * 4;
+                        data.copy_within(src..src + 4, dst);
+                    }
+                }
+            }
+            1 => {
+                console_log!("Applying vertical symmetry");
+                let row_len = width * 4;
+                for y in 0..height / 2 {
+                    let src = y * row_len;
+                    let dst = (height - 1 - y) * row_len;
+                    data.copy_within(src..src + row_len, dst);
+                }
+            }
+            _ => return Err(format!("axis must be 0 (horizontal) or 1 (vertical), got {}", axis)),
+        }
+        
+        Ok(())
+    }
+
+    /// 座標を検証して画素の先頭インデックスを計算
+    fn pixel_index(&self, data: &[u8], x: u32, y: u32) -> Result<usize, String> {
+        if x >= self.width || y >= self.height {
+            return Err(format!(
+                "pixel ({}, {}) is out of bounds for {}x{} image",
+                x, y, self.width, self.height
+            ));
+        }
+        let idx = (y as usize * self.width as usize + x as usize) * 4;
+        if idx + 4 > data.len() {
+            return Err(format!("data length {} is too short for pixel ({}, {})", data.len(), x, y));
+        }
+        Ok(idx)
+    }
+
+    /// 画素取得の本体
+    fn try_get_pixel(&self, data: &[u8], x: u32, y: u32) -> Result<Vec<u8>, String> {
+        let idx = self.pixel_index(data, x, y)?;
+        Ok(data[idx..idx + 4].to_vec())
+    }
+
+    /// 画素設定の本体
+    fn try_set_pixel(&self, data: &mut [u8], x: u32, y: u32, rgba: &[u8]) -> Result<(), String> {
+        if rgba.len() != 4 {
+            return Err(format!("rgba must have 4 values, got {}", rgba.len()));
+        }
+        let idx = self.pixel_index(data, x, y)?;
+        data[idx..idx + 4].copy_from_slice(rgba);
+        Ok(())
+    }
+
+    /// フレーム差分の本体
+    fn try_frame_difference(&self, prev: &[u8], curr: &mut [u8], threshold: u8) -> Result<(), String> {
+        validate_same_length(prev, curr)?;
+        
+        for (p, c) in prev.chunks_exact(4).zip(curr.chunks_exact_mut(4)) {
+            let changed = (0..3).any(|i| p[i].abs_diff(c[i]) > threshold);
+            if !changed {
+                c[..3].fill(0);
+            }
+            // Alpha値は保持
+        }
+        
+        Ok(())
+    }
+
+    /// 長辺を`max_dim`に合わせたサムネイルのサイズ (各辺は1以上)
+    fn thumbnail_size(&self, max_dim: u32) -> (usize, usize) {
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let longest = width.max(height).max(1);
+        let target = (max_dim.max(1) as usize).min(longest);
+        
+        let thumb_width = ((width * target + longest / 2) / longest).max(1);
+        let thumb_height = ((height * target + longest / 2) / longest).max(1);
+        (thumb_width, thumb_height)
+    }
+
+    /// 輝度のヒストグラム (256段階) を作成
+    fn luma_histogram(&self, data: &[u8]) -> [u32; 256] {
+        let mut histogram = [0u32; 256];
+        for pixel in data.chunks_exact(4) {
+            let luma = self.luma(pixel[0] as f32, pixel[1] as f32, pixel[2] as f32) as usize;
+            histogram[luma.min(255)] += 1;
+        }
+        histogram
+    }
+
+    /// 周波数レイヤー合成の本体
+    fn try_recombine_frequency(&self, low: &[u8], high: &[u8]) -> Result<Vec<u8>, String> {
+        validate_same_length(low, high)?;
+        
+        let mut output = low.to_vec();
+        for (pixel, high_pixel) in output.chunks_exact_mut(4).zip(high.chunks_exact(4)) {
+            for c in 0..3 {
+                let value = pixel[c] as i32 + (high_pixel[c] as i32 - 128) * 2;
+                pixel[c] = value.clamp(0, 255) as u8;
+            }
+        }
+        
+        Ok(output)
+    }
+
+    /// `channels`チャンネル (最大4) の浮動小数点バッファに分離型ブラーを適用
+    /// サンプル範囲は`blur_row_horizontal`・`blur_row_vertical`と同じで、
+    /// 同様に累積和を使うため計算量は半径によらない
+    fn box_blur_f32(&self, buffer: &mut [f32], width: usize, height: usize, channels: usize, radius: f32) {
+        // 水平・垂直の各パスの (ライン数, 1ラインの画素数, ライン間の刻み, 画素間の刻み)
+        let passes = [(height, width, width, 1), (width, height, 1, width)];
+        
+        for (lines, length, line_stride, pixel_stride) in passes {
+            let radius = radius.min(length as f32);
+            let kernel_size = (radius * 2.0) as usize + 1;
+            let offset = radius as i32;
+            // 長いラインでの丸め誤差の蓄積を避けるため累積和はf64で持つ
+            let mut prefix = vec![0.0f64; (length + 1) * channels];
+            
+            for line in 0..lines {
+                let index_at = |i: usize| (line * line_stride + i * pixel_stride) * channels;
+                
+                for i in 0..length {
+                    let idx = index_at(i);
+                    for c in 0..channels {
+                        prefix[(i + 1) * channels + c] = prefix[i * channels + c] + buffer[idx + c] as f64;
+                    }
+                }
+                
+                for i in 0..length {
+                    let start = (i as i32 - offset).max(0);
+                    let end = (i as i32 - offset + kernel_size as i32).min(length as i32);
+                    if start >= end {
+                        continue;
+                    }
+                    
+                    let (start, end) = (start as usize, end as usize);
+                    let count = (end - start) as f64;
+                    let idx = index_at(i);
+                    for c in 0..channels {
+                        let sum = prefix[end * channels + c] - prefix[start * channels + c];
+                        buffer[idx + c] = (sum / count) as f32;
+                    }
+                }
+            }
+        }
+    }
+
+    /// 色紙スケッチの本体
+    fn try_colored_sketch(&mut self, data: &mut [u8], paper: &[u8], ink: &[u8]) -> Result<(), String> {
+        if paper.len() != 3 {
+            return Err(format!("paper must be [r, g, b], got {} values", paper.len()));
+        }
+        if ink.len() != 3 {
+            return Err(format!("ink must be [r, g, b], got {} values", ink.len()));
+        }
+        
+        console_log!("Applying colored sketch");
+        
+        let sketch = self.pencil_sketch_luma(data);
+        for (pixel, &value) in data.chunks_exact_mut(4).zip(&sketch) {
+            let t = value as f32 / 255.0;
+            for c in 0..3 {
+                let color = ink[c] as f32 + (paper[c] as f32 - ink[c] as f32) * t;
+                pixel[c] = color.round().clamp(0.0, 255.0) as u8;
+            }
+            // Alpha値は保持
+        }
+        
+        Ok(())
+    }
+
+    /// 鉛筆画風の輝度 (白い紙に黒い線) を計算
+    /// グレースケールと、その反転をぼかしたものを覆い焼き合成する
+    fn pencil_sketch_luma(&self, data: &[u8]) -> Vec<u8> {
+        const SKETCH_BLUR_RADIUS: f32 = 3.0;
+        
+        let gray = self.gray_buffer(data);
+        let mut inverted: Vec<u8> = gray.iter().flat_map(|&g| [255 - g, 255 - g, 255 - g, 255]).collect();
+        self.sub_processor(self.width, self.height).gaussian_blur(&mut inverted, SKETCH_BLUR_RADIUS);
+        
+        gray.iter()
+            .zip(inverted.chunks_exact(4))
+            .map(|(&g, blurred)| {
+                // 覆い焼き (カラードッジ)
+                (g as u32 * 255)
+                    .checked_div(255 - blurred[0] as u32)
+                    .map_or(255, |v| v.min(255) as u8)
+            })
+            .collect()
+    }
+
+    /// タイル敷き詰めの本体
+    fn try_tile_fill(&self, src: &[u8], sw: u32, sh: u32) -> Result<Vec<u8>, String> {
+        if sw == 0 || sh == 0 {
+            return Err(format!("source size must be non-zero, got {}x{}", sw, sh));
+        }
+        let sw = sw as usize;
+        let sh = sh as usize;
+        if src.len() != sw * sh * 4 {
+            return Err(format!("src length must be {} (sw * sh * 4), got {}", sw * sh * 4, src.len()));
+        }
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let mut output = vec![0u8; width * height * 4];
+        for (i, pixel) in output.chunks_exact_mut(4).enumerate() {
+            let (x, y) = (i % width, i / width);
+            let idx = ((y % sh) * sw + x % sw) * 4;
+            pixel.copy_from_slice(&src[idx..idx + 4]);
+        }
+        
+        Ok(output)
+    }
+
+    /// エッジオーバーレイの本体
+    fn try_edge_overlay(&mut self, data: &mut [u8], color: &[u8], threshold: u8) -> Result<(), String> {
+        if color.len() != 3 {
+            return Err(format!("color must be [r, g, b], got {} values", color.len()));
+        }
+        
+        console_log!("Applying edge overlay with threshold: {}", threshold);
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let gray = self.gray_buffer(data);
+        let (gradient_x, gradient_y) = self.sobel_gradients(&gray, width, height);
+        
+        let threshold = threshold as f32;
+        for (i, (gx, gy)) in gradient_x.iter().zip(&gradient_y).enumerate() {
+            let magnitude = ((gx * gx + gy * gy) as f32).sqrt();
+            if magnitude > threshold {
+                data[i * 4..i * 4 + 3].copy_from_slice(color);
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// 輪郭線付きHDRアニメ調エフェクトの本体
+    fn try_hdr_anime_outlined(&mut self, data: &mut [u8], outline: &[u8], thickness: u32, threshold: u8) -> Result<(), String> {
+        let color: [u8; 3] = outline
+            .try_into()
+            .map_err(|_| format!("outline must be [r, g, b], got {} values", outline.len()))?;
+        
+        console_log!("Applying outlined HDR anime effect: thickness={}, threshold={}", thickness, threshold);
+        
+        self.hdr_anime_with_outline(data, EdgeOutline { color, thickness, threshold });
+        Ok(())
+    }
+
+    /// デフォルト値でHDRアニメ調エフェクトを適用 (輪郭線の設定のみ指定)
+    fn hdr_anime_with_outline(&mut self, data: &mut [u8], outline: EdgeOutline) {
+        self.apply_hdr_anime(
+            data,
+            DEFAULT_HDR_GAMMA,
+            DEFAULT_HDR_SATURATION,
+            DEFAULT_ANIME_LEVELS,
+            DEFAULT_EDGE_STRENGTH,
+            outline,
+        );
+    }
+
+    /// HDRアニメ調エフェクトの本体
+    fn apply_hdr_anime(&mut self, data: &mut [u8], gamma: f32, saturation: f32, levels: u32, edge: f32, outline: EdgeOutline) {
+        console_log!("Applying HDR anime effect");
+        
+        let width = self.width as usize;
+        let height = self.height as usize;
+        
+        // ステップ1: HDR強調処理
+        self.apply_hdr_enhancement(data, gamma, saturation);
+        
+        // ステップ2: アニメ調処理（色の階調化）
+        self.apply_anime_posterization(data, levels.max(2));
+        
+        // ステップ3: エッジ強調 (輪郭線指定時は輪郭線の描画)
+        self.apply_edge_enhancement(data, width, height, edge, outline);
+    }
+
+    /// JSONフィルタ列適用の本体
+    fn try_apply_ops(&mut self, data: &mut [u8], ops_json: &str) -> Result<(), String> {
+        let ops: serde_json::Value = serde_json::from_str(ops_json)
+            .map_err(|e| format!("invalid ops JSON: {}", e))?;
+        let ops = ops.as_array().ok_or("ops JSON must be an array")?;
+        
+        // 画像を変更する前にすべての操作を検証する
+        let ops = ops
+            .iter()
+            .enumerate()
+            .map(|(i, op)| parse_op(i, op))
+            .collect::<Result<Vec<Op>, String>>()?;
+        
+        for op in &ops {
+            (op.spec.apply)(self, data, &op.values);
+        }
+        
+        Ok(())
+    }
+
+    /// 領域平均色の本体
+    fn try_average_color(&self, data: &[u8], x: u32, y: u32, w: u32, h: u32) -> Result<Vec<u8>, String> {
+        if w == 0 || h == 0 {
+            return Err(format!("region must be non-empty, got {}x{}", w, h));
+        }
+        if x as u64 + w as u64 > self.width as u64 || y as u64 + h as u64 > self.height as u64 {
+            return Err(format!(
+                "region ({}, {}) {}x{} is out of bounds for {}x{} image",
+                x, y, w, h, self.width, self.height
+            ));
+        }
+        let width = self.width as usize;
+        if data.len() < (self.height as usize * width) * 4 {
+            return Err(format!("data length {} is too short for {}x{} image", data.len(), self.width, self.height));
+        }
+        
+        let mut sum = [0u64; 4];
+        for py in y as usize..(y + h) as usize {
+            for px in x as usize..(x + w) as usize {
+                let idx = (py * width + px) * 4;
+                for c in 0..4 {
+                    sum[c] += data[idx + c] as u64;
+                }
+            }
+        }
+        
+        let count = w as u64 * h as u64;
+        Ok(sum.iter().map(|&s| ((s + count / 2) / count) as u8).collect())
+    }
+
+    /// トライトーンの本体
+    fn try_tritone(&mut self, data: &mut [u8], shadow: &[u8], mid: &[u8], highlight: &[u8], low: u8, high: u8) -> Result<(), String> {
+        for (name, color) in [("shadow", shadow), ("mid", mid), ("highlight", highlight)] {
+            if color.len() != 3 {
+                return Err(format!("{} must be [r, g, b], got {} values", name, color.len()));
+            }
+        }
+        if low > high {
+            return Err(format!("low ({}) must not exceed high ({})", low, high));
+        }
+        
+        console_log!("Applying tritone: low={}, high={}", low, high);
+        
+        for pixel in data.chunks_exact_mut(4) {
+            let luma = self.luma(pixel[0] as f32, pixel[1] as f32, pixel[2] as f32) as u8;
+            let color = if luma < low {
+                shadow
+            } else if luma > high {
+                highlight
+            } else {
+                mid
+            };
+            pixel[..3].copy_from_slice(color);
+            // Alpha値は保持
+        }
+        
+        Ok(())
+    }
+
+    /// ポートレートブラーの本体
+    fn try_portrait_blur(&mut self, data: &mut [u8], mask: &[u8], radius: f32) -> Result<(), String> {
+        let pixel_count = (self.width * self.height) as usize;
+        if data.len() != pixel_count * 4 {
+            return Err(format!(
+                "data length must be {} (width * height * 4), got {}",
+                pixel_count * 4,
+                data.len()
+            ));
+        }
+        if mask.len() != pixel_count {
+            return Err(format!(
+                "mask length must be {} (width * height), got {}",
+                pixel_count,
+                mask.len()
+            ));
+        }
+        
+        console_log!("Applying portrait blur with radius: {}", radius);
+        
+        // 全体をぼかしてから、マスクの割合で元画像を重ねる
+        let mut blurred = data.to_vec();
+        self.gaussian_blur(&mut blurred, radius);
+        
+        for (i, &m) in mask.iter().enumerate() {
+            let t = m as f32 / 255.0;
+            let idx = i * 4;
+            for c in 0..3 {
+                let background = blurred[idx + c] as f32;
+                let subject = data[idx + c] as f32;
+                data[idx + c] = (background + (subject - background) * t).round() as u8;
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// 1画素にバイブランスを適用 (Alpha値は保持)
+    fn vibrance_pixel(&self, pixel: &mut [u8], amount: f32) {
+        let (hue, saturation, value) = rgb_to_hsv(pixel[0], pixel[1], pixel[2]);
+        
+        // 彩度が低いほど強く強調し、肌色は抑える
+        let mut boost = amount * (1.0 - saturation);
+        if is_skin_tone(hue, saturation, value) {
+            boost *= VIBRANCE_SKIN_FACTOR;
+        }
+        
+        let gray = self.luma(pixel[0] as f32, pixel[1] as f32, pixel[2] as f32);
+        for channel in pixel[..3].iter_mut() {
+            let v = *channel as f32;
+            *channel = (gray + (v - gray) * (1.0 + boost)).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+
+    /// 正規化済みカーネルによるブラーの本体
+    fn try_blur_with_kernel(&mut self, data: &mut [u8], kernel_1d: &[f32]) -> Result<(), String> {
+        // 手入力の係数の丸め誤差は許容する
+        const SUM_TOLERANCE: f32 = 1e-3;
+        
+        let sum: f32 = kernel_1d.iter().sum();
+        if (sum - 1.0).abs() > SUM_TOLERANCE {
+            return Err(format!("kernel weights must sum to 1, got {}", sum));
+        }
+        self.try_convolve_separable(data, kernel_1d)
+    }
+
+    /// 枠付き画像生成の本体
+    fn try_add_border(&self, data: &[u8], thickness: u32, color: [u8; 4]) -> Result<Vec<u8>, String> {
+        let width = self.width as usize;
+        let height = self.height as usize;
+        if data.len() != width * height * 4 {
+            return Err(format!(
+                "data length must be {} (width * height * 4), got {}",
+                width * height * 4,
+                data.len()
+            ));
+        }
+        
+        let t = thickness as usize;
+        let new_width = width + 2 * t;
+        let new_height = height + 2 * t;
+        
+        // 枠の色で塗りつぶしてから元画像をコピー
+        let mut output = color.repeat(new_width * new_height);
+        for y in 0..height {
+            let src = y * width * 4;
+            let dst = ((y + t) * new_width + t) * 4;
+            output[dst..dst + width * 4].copy_from_slice(&data[src..src + width * 4]);
+        }
+        
+        Ok(output)
+    }
+
+    /// マスク合成の本体
+    fn try_blend_with_mask(&self, a: &[u8], b: &[u8], mask: &[u8]) -> Result<Vec<u8>, String> {
+        validate_same_length(a, b)?;
+        let pixel_count = (self.width * self.height) as usize;
+        if a.len() != pixel_count * 4 {
+            return Err(format!(
+                "image length must be {} (width * height * 4), got {}",
+                pixel_count * 4,
+                a.len()
+            ));
+        }
+        if mask.len() != pixel_count {
+            return Err(format!(
+                "mask length must be {} (width * height), got {}",
+                pixel_count,
+                mask.len()
+            ));
+        }
+        
+        let mut output = Vec::with_capacity(a.len());
+        for ((pixel_a, pixel_b), &m) in a.chunks_exact(4).zip(b.chunks_exact(4)).zip(mask.iter()) {
+            let t = m as u32;
+            for c in 0..4 {
+                // 整数演算で丸めて0と255で端の値と完全に一致させる
+                let value = (pixel_a[c] as u32 * (255 - t) + pixel_b[c] as u32 * t + 127) / 255;
+                output.push(value as u8);
+            }
+        }
+        
+        Ok(output)
+    }
+}
+
+/// 閾値を超えるエッジを指定色・指定太さの輪郭線として描画
+/// 
+/// # Arguments
+/// * `data` - RGBA画像データ (mutable)
+/// * `gradient_x` / `gradient_y` - Sobel勾配 (width * height)
+/// * `outline` - 輪郭線の設定 (`thickness`は1以上)
+fn draw_edge_outline(data: &mut [u8], gradient_x: &[i32], gradient_y: &[i32], width: usize, height: usize, outline: EdgeOutline) {
+    let threshold = outline.threshold as f32;
+    let is_edge: Vec<bool> = gradient_x
+        .iter()
+        .zip(gradient_y)
+        .map(|(gx, gy)| ((gx * gx + gy * gy) as f32).sqrt() > threshold)
+        .collect();
+    
+    // エッジを太さ分だけ膨張させる (-before..=after の範囲)
+    let before = ((outline.thickness - 1) / 2) as usize;
+    let after = (outline.thickness / 2) as usize;
+    for y in 0..height {
+        for x in 0..width {
+            let near_edge = (y.saturating_sub(after)..(y + before + 1).min(height)).any(|ey| {
+                (x.saturating_sub(after)..(x + before + 1).min(width)).any(|ex| is_edge[ey * width + ex])
+            });
+            if near_edge {
+                let idx = (y * width + x) * 4;
+                data[idx..idx + 3].copy_from_slice(&outline.color);
+            }
+        }
+    }
+}
+
+/// 画素単位 (u32) で処理する高速パス
+/// RGBAの4バイトをリトルエンディアンのu32としてまとめて読み書きし、
+/// チャンネルをまとめて変換できるようにする (R = 下位8ビット, A = 上位8ビット)
+/// 
+/// # Errors
+/// データ長が4の倍数でない場合はエラーを返し、データは変更しない
+fn for_each_pixel_u32<F: Fn(u32) -> u32 + Sync>(data: &mut [u8], f: F) -> Result<(), String> {
+    if !data.len().is_multiple_of(4) {
+        return Err(format!("data length must be a multiple of 4, got {}", data.len()));
+    }
+    
+    let process = |bytes: &mut [u8]| {
+        for pixel_bytes in bytes.chunks_exact_mut(4) {
+            let pixel = u32::from_le_bytes([pixel_bytes[0], pixel_bytes[1], pixel_bytes[2], pixel_bytes[3]]);
+            pixel_bytes.copy_from_slice(&f(pixel).to_le_bytes());
+        }
+    };
+    
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        data.par_chunks_mut(PARALLEL_CHUNK_PIXELS * 4).for_each(process);
+    }
+    #[cfg(not(feature = "parallel"))]
+    process(data);
+    
+    Ok(())
+}
+
+/// 1画素にセピア変換行列を適用 (Alpha値は保持)
+fn sepia_pixel(pixel: u32) -> u32 {
+    let [r, g, b, a] = pixel.to_le_bytes();
+    let r = r as f32;
+    let g = g as f32;
+    let b = b as f32;
+    
+    // セピア変換行列
+    let new_r = (r * 0.393 + g * 0.769 + b * 0.189).min(255.0) as u8;
+    let new_g = (r * 0.349 + g * 0.686 + b * 0.168).min(255.0) as u8;
+    let new_b = (r * 0.272 + g * 0.534 + b * 0.131).min(255.0) as u8;
+    
+    u32::from_le_bytes([new_r, new_g, new_b, a])
+}
+
+/// リトルエンディアンで解釈した画素のRとBを入れ替える
+fn swap_red_blue(pixel: u32) -> u32 {
+    let [r, g, b, a] = pixel.to_le_bytes();
+    u32::from_le_bytes([b, g, r, a])
+}
+
+/// 画素 (4バイト) 単位で処理
+/// `parallel`フィーチャー有効時はrayonで分割して並列に処理する。
+/// 末尾の4バイトに満たない端数は変更しない
+fn for_each_pixel<F: Fn(&mut [u8]) + Sync>(data: &mut [u8], f: F) {
+    let process = |bytes: &mut [u8]| bytes.chunks_exact_mut(4).for_each(&f);
+    
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        data.par_chunks_mut(PARALLEL_CHUNK_PIXELS * 4).for_each(process);
+    }
+    #[cfg(not(feature = "parallel"))]
+    process(data);
+}
+
+/// 画像を行単位に分割して処理
+/// `parallel`フィーチャー有効時はrayonで各行を並列に処理する
+/// 
+/// # Arguments
+/// * `data` - RGBA画像データ (mutable)
+/// * `row_len` - 1行のバイト数 (0の場合は何もしない)
+/// * `f` - (行番号, 行データ) を受け取る処理
+fn for_each_row<F: Fn(usize, &mut [u8]) + Sync>(data: &mut [u8], row_len: usize, f: F) {
+    if row_len == 0 {
+        return;
+    }
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        data.par_chunks_mut(row_len).enumerate().for_each(|(y, row)| f(y, row));
+    }
+    #[cfg(not(feature = "parallel"))]
+    for_each_row_serial(data, row_len, f);
+}
+
+/// 画像を行単位に分割して逐次処理
+#[cfg(any(test, not(feature = "parallel")))]
+fn for_each_row_serial<F: Fn(usize, &mut [u8])>(data: &mut [u8], row_len: usize, f: F) {
+    for (y, row) in data.chunks_mut(row_len).enumerate() {
+        f(y, row);
+    }
+}
+
+/// 2つのバッファの長さが等しいことを検証
+fn validate_same_length(a: &[u8], b: &[u8]) -> Result<(), String> {
+    if a.len() != b.len() {
+        return Err(format!("buffer lengths differ: {} vs {}", a.len(), b.len()));
+    }
+    Ok(())
+}
+
+/// 列方向の累積和を計算 (垂直方向ブラー用)
+/// 
+/// # Returns
+/// `(height + 1) * width * channels`要素。`[y * width * channels + x * channels + c]`は
+/// 列xのy行目より上の画素の合計
+fn column_prefix_sums(data: &[u8], width: usize, height: usize, channels: usize) -> Vec<u32> {
+    let line = width * channels;
+    let mut sums = vec![0u32; (height + 1) * line];
+    for y in 0..height {
+        for x in 0..width {
+            let idx = (y * width + x) * 4;
+            for c in 0..channels {
+                let i = x * channels + c;
+                sums[(y + 1) * line + i] = sums[y * line + i] + data[idx + c] as u32;
+            }
+        }
+    }
+    sums
+}
+
+/// ポスタリゼーションの階調数 (2以上) を検証
+fn validate_levels(levels: u32) -> Result<(), String> {
+    if levels < 2 {
+        return Err(format!("levels must be at least 2, got {}", levels));
+    }
+    Ok(())
+}
+
+/// 組織的ディザリングの階調数とBayer行列のサイズを検証
+fn validate_dither(levels: u32, matrix_size: u32) -> Result<(), String> {
+    validate_levels(levels)?;
+    if ![2, 4, 8].contains(&matrix_size) {
+        return Err(format!("matrix_size must be 2, 4 or 8, got {}", matrix_size));
+    }
+    Ok(())
+}
+
+/// 現在時刻をミリ秒で取得 (performance.now()を使用)
+#[cfg(all(target_arch = "wasm32", feature = "timing"))]
+fn now_ms() -> f64 {
+    web_sys::window()
+        .and_then(|window| window.performance())
+        .map(|performance| performance.now())
+        .unwrap_or(0.0)
+}
+
+/// 計測手段がない場合は常に0.0を返す
+#[cfg(all(target_arch = "wasm32", not(feature = "timing")))]
+fn now_ms() -> f64 {
+    0.0
+}
+
+/// ネイティブ環境では単調増加クロックを使用
+#[cfg(not(target_arch = "wasm32"))]
+fn now_ms() -> f64 {
+    use std::sync::OnceLock;
+    use std::time::Instant;
+    
+    static ORIGIN: OnceLock<Instant> = OnceLock::new();
+    ORIGIN.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0
+}
+
+/// RGBをHSVに変換
+/// 
+/// # Returns
+/// (色相 0.0..360.0, 彩度 0.0..1.0, 明度 0.0..1.0)
+fn rgb_to_hsv(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
+    let r = r as f32 / 255.0;
+    let g = g as f32 / 255.0;
+    let b = b as f32 / 255.0;
+    
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    
+    let hue = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let saturation = if max == 0.0 { 0.0 } else { delta / max };
+    
+    (hue, saturation, max)
+}
+
+/// HSVをRGBに変換 (`rgb_to_hsv`の逆変換)
+/// 
+/// # Arguments
+/// * `hue` - 色相 (度、範囲外は0.0..360.0に折り返す)
+/// * `saturation` - 彩度 (0.0..1.0)
+/// * `value` - 明度 (0.0..1.0)
+fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> [u8; 3] {
+    let chroma = value * saturation;
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    [r, g, b].map(|c| ((c + m) * 255.0).round().clamp(0.0, 255.0) as u8)
+}
+
+/// 肌色の色相・彩度・明度範囲かどうかを判定
+fn is_skin_tone(hue: f32, saturation: f32, value: f32) -> bool {
+    hue <= 50.0 && (0.15..=0.7).contains(&saturation) && value >= 0.3
+}
+
+/// 2つの色相の円周上の距離 (0.0..180.0)
+fn hue_distance(a: f32, b: f32) -> f32 {
+    let d = (a - b).rem_euclid(360.0);
+    d.min(360.0 - d)
+}
+
+/// 標準偏差`sigma`の正規化済みガウシアンカーネルを作成 (±3σで打ち切り)
+fn build_gaussian_kernel(sigma: f32) -> Vec<f32> {
+    let radius = (sigma * 3.0).ceil() as i32;
+    let denom = 2.0 * sigma * sigma;
+    let kernel: Vec<f32> = (-radius..=radius)
+        .map(|i| (-((i * i) as f32) / denom).exp())
+        .collect();
+    let sum: f32 = kernel.iter().sum();
+    kernel.into_iter().map(|w| w / sum).collect()
+}
+
+/// 制御点からトーンカーブのLUTを作成
+/// 最初の制御点より前・最後の制御点より後は端の出力値で一定とする
+fn build_tone_lut(points: &[f32]) -> Result<[u8; 256], String> {
+    if points.len() < 4 || !points.len().is_multiple_of(2) {
+        return Err(format!("points must contain at least two (x, y) pairs, got {} values", points.len()));
+    }
+    if points.iter().any(|v| !(0.0..=255.0).contains(v)) {
+        return Err("points must be within 0..255".to_string());
+    }
+    let pairs: Vec<(f32, f32)> = points.chunks_exact(2).map(|p| (p[0], p[1])).collect();
+    if pairs.windows(2).any(|w| w[0].0 >= w[1].0) {
+        return Err("point inputs must be strictly increasing".to_string());
+    }
+    
+    let mut lut = [0u8; 256];
+    for (v, entry) in lut.iter_mut().enumerate() {
+        let x = v as f32;
+        let y = match pairs.iter().position(|&(px, _)| px >= x) {
+            Some(0) => pairs[0].1,
+            Some(i) => {
+                // 前後の制御点で線形補間
+                let (x0, y0) = pairs[i - 1];
+                let (x1, y1) = pairs[i];
+                y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+            }
+            None => pairs[pairs.len() - 1].1,
+        };
+        *entry = y.round().clamp(0.0, 255.0) as u8;
+    }
+    
+    Ok(lut)
+}
+
+/// 色の配列から輝度0..255に対応するグラデーションLUTを作成
+fn build_gradient_lut(stops: &[u8]) -> Result<[[u8; 3]; 256], String> {
+    if stops.len() < 6 || !stops.len().is_multiple_of(3) {
+        return Err(format!("stops must contain at least two RGB triples, got {} values", stops.len()));
+    }
+    
+    let colors: Vec<[u8; 3]> = stops.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect();
+    Ok(interpolate_gradient(&colors))
+}
+
+/// 2色以上の色の並びを線形補間した256要素のLUTを作成
+fn interpolate_gradient(colors: &[[u8; 3]]) -> [[u8; 3]; 256] {
+    let segments = (colors.len() - 1) as f32;
+    
+    let mut lut = [[0u8; 3]; 256];
+    for (v, entry) in lut.iter_mut().enumerate() {
+        // 隣接する2色の間で線形補間
+        let position = v as f32 / 255.0 * segments;
+        let i = (position as usize).min(colors.len() - 2);
+        let t = position - i as f32;
+        for c in 0..3 {
+            let from = colors[i][c] as f32;
+            let to = colors[i + 1][c] as f32;
+            entry[c] = (from + (to - from) * t).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+    
+    lut
+}
+
+/// フィルタが参照する近傍の最大距離 (タイル処理のハロー幅)
+fn filter_halo(filter: Filter) -> usize {
+    match filter {
+        Filter::GaussianBlur => DEFAULT_BLUR_RADIUS.ceil() as usize,
+        Filter::HdrAnime | Filter::EdgeDetection | Filter::Sharpen => 1,
+        Filter::SepiaTone | Filter::Negative => 0,
+    }
+}
+
+/// フィルタの1画素あたりのおおよその演算回数 (estimate_cost用)
+fn filter_cost_per_pixel(filter: Filter) -> u64 {
+    match filter {
+        // 水平・垂直の2パス x (累積和の加算 + 区間和の減算と除算) x RGB (半径によらない)
+        Filter::GaussianBlur => 2 * 3 * 3,
+        // HDR強調 + 階調化 + グレースケール化とSobel (3x3 x 2方向) によるエッジ強調
+        Filter::HdrAnime => 12 + 3 + 18 + 6,
+        // グレースケール化とSobel (3x3 x 2方向)
+        Filter::EdgeDetection => 3 + 18,
+        // 3x3カーネル x RGB
+        Filter::Sharpen => 9 * 3,
+        // 3x3行列
+        Filter::SepiaTone => 9,
+        // 32bit単位のXOR
+        Filter::Negative => 1,
+    }
+}
+
+/// n x n のBayer行列を生成 (nは2の累乗)
+fn bayer_matrix(n: usize) -> Vec<u32> {
+    let mut matrix = vec![0u32];
+    let mut size = 1;
+    while size < n {
+        // M(2n) = [[4M, 4M+2], [4M+3, 4M+1]]
+        let next_size = size * 2;
+        let mut next = vec![0u32; next_size * next_size];
+        for y in 0..size {
+            for x in 0..size {
+                let v = matrix[y * size + x] * 4;
+                next[y * next_size + x] = v;
+                next[y * next_size + x + size] = v + 2;
+                next[(y + size) * next_size + x] = v + 3;
+                next[(y + size) * next_size + x + size] = v + 1;
+            }
+        }
+        matrix = next;
+        size = next_size;
+    }
+    matrix
+}
+
+/// 決定的な疑似乱数生成器 (xorshift32)
+/// 同じシードから常に同じ乱数列を生成する
+struct XorShift32 {
+    state: u32,
+}
+
+impl XorShift32 {
+    /// シードから生成器を作成 (0は固定値に置き換える)
+    fn new(seed: u32) -> XorShift32 {
+        XorShift32 { state: if seed == 0 { 0x9E37_79B9 } else { seed } }
+    }
+
+    /// 次の乱数を取得
+    fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    /// 0.0..1.0 の乱数を取得
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
+
+/// R・G・B各チャンネルのヒストグラム (256段階) を作成
+fn channel_histograms(data: &[u8]) -> [[u32; 256]; 3] {
+    let mut histograms = [[0u32; 256]; 3];
+    for pixel in data.chunks_exact(4) {
+        for c in 0..3 {
+            histograms[c][pixel[c] as usize] += 1;
+        }
+    }
+    histograms
+}
+
+/// デコード済みの画像 (RGBAデータとサイズ)
+#[cfg(feature = "image")]
+#[wasm_bindgen]
+pub struct DecodedImage {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+#[cfg(feature = "image")]
+#[wasm_bindgen]
+impl DecodedImage {
+    /// 画像の幅
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// 画像の高さ
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// RGBA画像データ
+    #[wasm_bindgen(getter)]
+    pub fn data(&self) -> Vec<u8> {
+        self.data.clone()
+    }
+}
+
+/// JPEG/PNGのバイト列をRGBA画像データにデコード
+/// 返されたサイズで`ImageProcessor`を作成すればそのままフィルタを適用できる
+/// 
+/// # Arguments
+/// * `bytes` - エンコードされた画像のバイト列
+/// 
+/// # Errors
+/// 対応していない形式、または壊れたデータの場合
+#[cfg(feature = "image")]
+#[wasm_bindgen]
+pub fn decode_image(bytes: &[u8]) -> Result<DecodedImage, JsError> {
+    try_decode_image(bytes).map_err(|e| JsError::new(&e))
+}
+
+/// 画像デコードの本体
+#[cfg(feature = "image")]
+fn try_decode_image(bytes: &[u8]) -> Result<DecodedImage, String> {
+    let image = image::load_from_memory(bytes)
+        .map_err(|e| format!("failed to decode image: {}", e))?
+        .into_rgba8();
+    
+    Ok(DecodedImage {
+        width: image.width(),
+        height: image.height(),
+        data: image.into_raw(),
+    })
+}
+
+/// sRGBの8bit値をリニア値 (0.0..1.0) に変換
+/// sRGBの区分的な伝達関数 (IEC 61966-2-1) を使用する
+/// 
+/// # Arguments
+/// * `c` - sRGBのチャンネル値
+#[wasm_bindgen]
+pub fn srgb_to_linear(c: u8) -> f32 {
+    let v = c as f32 / 255.0;
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// リニア値 (0.0..1.0) をsRGBの8bit値に変換
+/// 
+/// # Arguments
+/// * `v` - リニアなチャンネル値 (範囲外はクランプ)
+#[wasm_bindgen]
+pub fn linear_to_srgb(v: f32) -> u8 {
+    let v = v.clamp(0.0, 1.0);
+    let encoded = if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    };
+    (encoded * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
+/// 範囲外のインデックスを端で鏡像反転して`0..n`に収める (端の画素は繰り返さない)
+fn reflect_index(i: i64, n: usize) -> usize {
+    if n == 1 {
+        return 0;
+    }
+    let period = 2 * (n as i64 - 1);
+    let i = i.rem_euclid(period);
+    if i < n as i64 {
+        i as usize
+    } else {
+        (period - i) as usize
+    }
+}
+
+/// apply_opsの`i`番目の操作をパースして検証
+fn parse_op(i: usize, op: &serde_json::Value) -> Result<Op, String> {
+    let filter = op
+        .get("filter")
+        .and_then(|f| f.as_str())
+        .ok_or_else(|| format!("op {} must have a string \"filter\"", i))?;
+    let empty = serde_json::Map::new();
+    let params = match op.get("params") {
+        None | Some(serde_json::Value::Null) => &empty,
+        Some(serde_json::Value::Object(params)) => params,
+        Some(_) => return Err(format!("op {} \"params\" must be an object", i)),
+    };
+    
+    build_op(filter, params).map_err(|e| format!("op {} ({}): {}", i, filter, e))
+}
+
+/// フィルタ名とパラメータから操作を作成
+/// 省略されたパラメータは`FILTER_CATALOG`のデフォルト値を使用する
+fn build_op(filter: &str, params: &serde_json::Map<String, serde_json::Value>) -> Result<Op, String> {
+    let spec = FILTER_CATALOG
+        .iter()
+        .find(|spec| spec.name == filter)
+        .ok_or_else(|| format!("unknown filter \"{}\"", filter))?;
+    let values = spec
+        .params
+        .iter()
+        .map(|param| match params.get(param.name) {
+            None => Ok(param.default),
+            Some(value) => value
+                .as_f64()
+                .map(|v| v as f32)
+                .ok_or_else(|| format!("param \"{}\" must be a number", param.name)),
+        })
+        .collect::<Result<Vec<f32>, String>>()?;
+    
+    (spec.validate)(&values)?;
+    Ok(Op { spec, values })
+}
+
+/// apply_opsで指定できるフィルタとパラメータの一覧をJSONで取得
+/// UIでスライダーなどの操作部品を自動生成する用途を想定している
+/// 
+/// # Returns
+/// `[{ "name": "gaussian_blur", "params": [{ "name": "radius", "type": "number",
+/// "default": 3.0, "min": 0.0, "max": 50.0 }] }, ...]` 形式のJSON文字列
+#[wasm_bindgen]
+pub fn describe_filters() -> String {
+    let filters: Vec<serde_json::Value> = FILTER_CATALOG
+        .iter()
+        .map(|filter| {
+            let params: Vec<serde_json::Value> = filter
+                .params
+                .iter()
+                .map(|spec| {
+                    serde_json::json!({
+                        "name": spec.name,
+                        "type": if spec.integer { "integer" } else { "number" },
+                        "default": spec.default,
+                        "min": spec.min,
+                        "max": spec.max,
+                    })
+                })
+                .collect();
+            serde_json::json!({ "name": filter.name, "params": params })
+        })
+        .collect();
+    
+    serde_json::Value::Array(filters).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// テスト用の小さな画像データを作成
+    fn create_test_image_data(width: u32, height: u32) -> Vec<u8> {
+        let mut data = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                // グラデーション画像を作成
+                let r = (x * 255 / width) as u8;
+                let g = (y * 255 / height) as u8;
+                let b = 128;
+                let a = 255;
+                data.extend_from_slice(&[r, g, b, a]);
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn test_image_processor_creation() {
+        let processor = ImageProcessor::new(100, 100);
+        assert_eq!(processor.width, 100);
+        assert_eq!(processor.height, 100);
+    }
+
+    // WebAssembly固有の機能を使わない純粋なロジックテスト
+    
+    #[test]
+    fn test_gaussian_blur_preserves_data_length() {
+        let mut processor = ImageProcessor::new(10, 10);
+        let mut data = create_test_image_data(10, 10);
+        let original_len = data.len();
+        
+        processor.gaussian_blur(&mut data, 2.0);
+        
+        assert_eq!(data.len(), original_len);
+        // Alpha値は変更されないことを確認
+        for i in (0..data.len()).step_by(4) {
+            assert_eq!(data[i + 3], 255);
+        }
+    }
+
+    #[test]
+    fn test_gaussian_blur_zero_radius() {
+        let mut processor = ImageProcessor::new(5, 5);
+        let mut data = create_test_image_data(5, 5);
+        let original_data = data.clone();
+        
+        processor.gaussian_blur(&mut data, 0.0);
+        
+        // 半径0の場合、データは変更されない
+        assert_eq!(data, original_data);
+    }
+
+    #[test]
+    fn test_sepia_tone_effect() {
+        let mut processor = ImageProcessor::new(2, 2);
+        let mut data = vec![
+            255, 0, 0, 255,    // 赤
+            0, 255, 0, 255,    // 緑
+            0, 0, 255, 255,    // 青
+            255, 255, 255, 255 // 白
+        ];
+        
+        processor.sepia_tone(&mut data);
+        
+        // セピア効果が適用されていることを確認
+        // 赤い画素がセピア調になっているか
+        assert!(data[0] > 0);  // R成分
+        assert!(data[1] > 0);  // G成分
+        assert_eq!(data[3], 255); // Alpha値は保持
+    }
+
+    #[test]
+    fn test_negative_effect() {
+        let mut processor = ImageProcessor::new(2, 1);
+        let mut data = vec![
+            255, 0, 128, 255,  // テスト色
+            100, 200, 50, 255  // テスト色
+        ];
+        let original_data = data.clone();
+        
+        processor.negative(&mut data);
+        
+        // ネガ効果の確認
+        assert_eq!(data[0], 255 - original_data[0]); // R反転
+        assert_eq!(data[1], 255 - original_data[1]); // G反転
+        assert_eq!(data[2], 255 - original_data[2]); // B反転
+        assert_eq!(data[3], original_data[3]);       // Alpha保持
+        
+        assert_eq!(data[4], 255 - original_data[4]); // R反転
+        assert_eq!(data[5], 255 - original_data[5]); // G反転
+        assert_eq!(data[6], 255 - original_data[6]); // B反転
+        assert_eq!(data[7], original_data[7]);       // Alpha保持
+    }
+
+    #[test]
+    fn test_get_gray_value() {
+        let processor = ImageProcessor::new(3, 1);
+        let data = vec![
+            255, 0, 0, 255,    // 赤
+            0, 255, 0, 255,    // 緑
+            0, 0, 255, 255,    // 青
+        ];
+        
+        let gray_red = processor.get_gray_value(&data, 0, 0, 3);
+        let gray_green = processor.get_gray_value(&data, 1, 0, 3);
+        let gray_blue = processor.get_gray_value(&data, 2, 0, 3);
+        
+        // グレースケール変換の妥当性確認
+        assert!(gray_red > 0);
+        assert!(gray_green > gray_red);  // 緑は赤より明るい
+        assert!(gray_blue > 0);
+    }
+
+    #[test]
+    fn test_data_integrity_after_multiple_effects() {
+        let mut processor = ImageProcessor::new(10, 10);
+        let mut data = create_test_image_data(10, 10);
+        let original_len = data.len();
+        
+        // 複数のエフェクトを順次適用
+        processor.gaussian_blur(&mut data, 1.0);
+        processor.sepia_tone(&mut data);
+        
+        // データ長とAlpha値の整合性確認
+        assert_eq!(data.len(), original_len);
+        for i in (0..data.len()).step_by(4) {
+            assert_eq!(data[i + 3], 255);
+        }
+    }
+
+    #[test]
+    fn test_blur_horizontal_basic() {
+        let processor = ImageProcessor::new(3, 1);
+        let mut data = vec![
+            255, 0, 0, 255,    // 赤
+            0, 255, 0, 255,    // 緑  
+            0, 0, 255, 255,    // 青
+        ];
+        
+        processor.blur_horizontal(&mut data, 3, 1, 1.0);
+        
+        // ブラー後もAlpha値は保持
+        assert_eq!(data[3], 255);
+        assert_eq!(data[7], 255);
+        assert_eq!(data[11], 255);
+    }
+
+    #[test]
+    fn test_count_clipped() {
+        let processor = ImageProcessor::new(4, 1);
+        let data = vec![
+            255, 128, 128, 255, // 白飛び (R=255)
+            128, 0, 128, 255,   // 黒つぶれ (G=0)
+            128, 128, 128, 0,   // Alphaのみ0はクリップ扱いしない
+            10, 20, 30, 255,    // 正常
+        ];
+        
+        assert_eq!(processor.count_clipped(&data), 2);
+    }
+
+    #[test]
+    fn test_kuwahara_flat_region_unchanged() {
+        let mut processor = ImageProcessor::new(6, 6);
+        let mut data = [100, 150, 200, 255].repeat(36);
+        let original_data = data.clone();
+        
+        processor.kuwahara(&mut data, 2);
+        
+        // データ長は保持され、均一領域は変化しない
+        assert_eq!(data.len(), original_data.len());
+        assert_eq!(data, original_data);
+    }
+
+    #[test]
+    fn test_hdr_anime_defaults_match_previous_output() {
+        let mut processor = ImageProcessor::new(3, 3);
+        let mut data = create_test_image_data(3, 3);
+        let mut tuned_data = data.clone();
+        
+        processor.hdr_anime(&mut data);
+        processor.hdr_anime_tuned(&mut tuned_data, 0.7, 1.8, 6, 0.5);
+        
+        // パラメータ化前の出力と一致することを確認
+        let expected = vec![
+            0, 0, 255, 255, 153, 0, 255, 255, 255, 0, 204, 255,
+            0, 153, 204, 255, 191, 191, 255, 255, 204, 102, 153, 255,
+            0, 255, 153, 255, 102, 204, 153, 255, 204, 204, 153, 255,
+        ];
+        assert_eq!(data, expected);
+        assert_eq!(tuned_data, expected);
+    }
+
+    #[test]
+    fn test_sharpen_amount_zero_is_identity() {
+        let mut processor = ImageProcessor::new(5, 5);
+        let mut data = create_test_image_data(5, 5);
+        let original_data = data.clone();
+        
+        processor.sharpen_amount(&mut data, 0.0);
+        
+        // amount 0.0 の場合、データは変更されない
+        assert_eq!(data, original_data);
+    }
+
+    #[test]
+    fn test_gaussian_blur_single_pixel_large_radius() {
+        let mut processor = ImageProcessor::new(1, 1);
+        let mut data = vec![200, 100, 50, 255];
+        
+        processor.gaussian_blur(&mut data, 3.0);
+        
+        // 1x1画像では自身のみがサンプルされ、値は変化しない
+        assert_eq!(data, vec![200, 100, 50, 255]);
+    }
+
+    #[test]
+    fn test_apply_timed_returns_non_negative() {
+        let mut processor = ImageProcessor::new(8, 8);
+        let mut data = create_test_image_data(8, 8);
+        let mut expected = data.clone();
+        
+        let elapsed = processor.apply_timed(&mut data, Filter::Negative);
+        processor.negative(&mut expected);
+        
+        // フィルタが適用され、経過時間は非負
+        assert!(elapsed >= 0.0);
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn test_edge_detection_denoised_suppresses_faint_gradient() {
+        let mut processor = ImageProcessor::new(5, 5);
+        // 1画素ごとに輝度が1ずつ変化する微弱なグラデーション
+        let mut data = Vec::new();
+        for _y in 0..5 {
+            for x in 0..5 {
+                let v = 100 + x as u8;
+                data.extend_from_slice(&[v, v, v, 255]);
+            }
+        }
+        let mut plain = data.clone();
+        
+        processor.edge_detection(&mut plain);
+        processor.edge_detection_denoised(&mut data, 20);
+        
+        // 通常のエッジ検出では微弱な強度が残るが、ノイズフロア以下は黒になる
+        let center = (2 * 5 + 2) * 4;
+        assert!(plain[center] > 0);
+        for y in 1..4 {
+            for x in 1..4 {
+                let idx = (y * 5 + x) * 4;
+                assert_eq!(&data[idx..idx + 3], &[0, 0, 0]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tilt_shift_keeps_focus_band() {
+        let mut processor = ImageProcessor::new(10, 10);
+        // 縦縞パターン (ブラーで値が変化する)
+        let mut data = Vec::new();
+        for _y in 0..10 {
+            for x in 0..10 {
+                let v = if x % 2 == 0 { 0 } else { 255 };
+                data.extend_from_slice(&[v, v, v, 255]);
+            }
+        }
+        let original_data = data.clone();
+        
+        processor.tilt_shift(&mut data, 4, 2, 3.0);
+        
+        // ピント帯 (4-5行目) は変化しない
+        let row_len = 10 * 4;
+        assert_eq!(&data[4 * row_len..6 * row_len], &original_data[4 * row_len..6 * row_len]);
+        // 端の行はぼかされる
+        assert_ne!(&data[0..row_len], &original_data[0..row_len]);
+        assert_ne!(&data[9 * row_len..], &original_data[9 * row_len..]);
+    }
+
+    #[test]
+    fn test_add_border() {
+        let processor = ImageProcessor::new(2, 2);
+        let data = [10, 20, 30, 255].repeat(4);
+        
+        let output = processor.try_add_border(&data, 1, [255, 0, 0, 255]).unwrap();
+        
+        // 2x2 -> 4x4
+        assert_eq!(processor.bordered_dimensions(1), vec![4, 4]);
+        assert_eq!(output.len(), 4 * 4 * 4);
+        for y in 0..4 {
+            for x in 0..4 {
+                let idx = (y * 4 + x) * 4;
+                let inside = (1..3).contains(&x) && (1..3).contains(&y);
+                let expected: &[u8] = if inside { &[10, 20, 30, 255] } else { &[255, 0, 0, 255] };
+                assert_eq!(&output[idx..idx + 4], expected);
+            }
+        }
+        
+        assert!(processor.try_add_border(&data[..8], 1, [255, 0, 0, 255]).is_err());
+    }
+
+    #[test]
+    fn test_color_isolate() {
+        let mut processor = ImageProcessor::new(2, 1);
+        let mut data = vec![
+            220, 20, 20, 255,  // 赤 (対象)
+            20, 20, 220, 255,  // 青 (対象外)
+        ];
+        
+        processor.color_isolate(&mut data, 0.0, 30.0);
+        
+        // 赤はそのまま、青はグレースケールになる
+        assert_eq!(&data[0..4], &[220, 20, 20, 255]);
+        assert_eq!(data[4], data[5]);
+        assert_eq!(data[5], data[6]);
+        assert_eq!(data[7], 255);
+    }
+
+    #[test]
+    fn test_rgb_to_hsv() {
+        let (h, s, v) = rgb_to_hsv(255, 0, 0);
+        assert_eq!((h, s, v), (0.0, 1.0, 1.0));
+        let (h, _, _) = rgb_to_hsv(0, 255, 0);
+        assert_eq!(h, 120.0);
+        let (h, _, _) = rgb_to_hsv(0, 0, 255);
+        assert_eq!(h, 240.0);
+        // 色相の距離は円周上で計算する
+        assert_eq!(hue_distance(350.0, 10.0), 20.0);
+    }
+
+    #[test]
+    fn test_posterize_levels() {
+        let mut processor = ImageProcessor::new(2, 1);
+        let mut data = vec![
+            0, 64, 100, 255,
+            200, 255, 30, 255,
+        ];
+        
+        // levels==1 はゼロ除算になるためエラー
+        assert!(processor.try_posterize(&mut data.clone(), 1).is_err());
+        
+        // levels==3 では 0, 127, 255 の3階調になる
+        processor.try_posterize(&mut data, 3).unwrap();
+        assert_eq!(data, vec![
+            0, 127, 127, 255,
+            255, 255, 0, 255,
+        ]);
+    }
+
+    #[test]
+    fn test_color_matrix_reproduces_sepia() {
+        let mut processor = ImageProcessor::new(10, 10);
+        let mut data = create_test_image_data(10, 10);
+        let mut sepia_data = data.clone();
+        let sepia_matrix = [
+            0.393, 0.769, 0.189, 0.0,
+            0.349, 0.686, 0.168, 0.0,
+            0.272, 0.534, 0.131, 0.0,
+        ];
+        
+        processor.try_color_matrix(&mut data, &sepia_matrix).unwrap();
+        processor.sepia_tone(&mut sepia_data);
+        
+        // sepia_toneの出力と±1以内で一致
+        for (a, b) in data.iter().zip(sepia_data.iter()) {
+            assert!((*a as i32 - *b as i32).abs() <= 1);
+        }
+        
+        // 不正な要素数はエラー
+        assert!(processor.try_color_matrix(&mut data, &[1.0; 9]).is_err());
+    }
+
+    #[test]
+    fn test_convolve_separable_matches_box_blur() {
+        let mut processor = ImageProcessor::new(8, 8);
+        let mut data = create_test_image_data(8, 8);
+        let mut box_data = data.clone();
+        
+        processor.try_convolve_separable(&mut data, &[1.0 / 3.0; 3]).unwrap();
+        processor.gaussian_blur(&mut box_data, 1.0);
+        
+        // 境界を除く内部画素はボックスブラーと±1以内で一致
+        for y in 1..7 {
+            for x in 1..7 {
+                let idx = (y * 8 + x) * 4;
+                for c in 0..4 {
+                    assert!((data[idx + c] as i32 - box_data[idx + c] as i32).abs() <= 1);
+                }
+            }
+        }
+        
+        // 偶数長のカーネルはエラー
+        assert!(processor.try_convolve_separable(&mut data, &[0.5, 0.5]).is_err());
+    }
+
+    #[test]
+    fn test_contrast_luma_preserves_hue_ratio() {
+        let mut processor = ImageProcessor::new(1, 1);
+        let mut data = vec![150, 90, 30, 255];
+        
+        processor.contrast_luma(&mut data, 1.5);
+        
+        // 輝度は変化するが、チャンネル間の比率は保たれる
+        assert_ne!(data[0], 150);
+        let ratio_rg = data[0] as f32 / data[1] as f32;
+        let ratio_gb = data[1] as f32 / data[2] as f32;
+        assert!((ratio_rg - 150.0 / 90.0).abs() < 0.05);
+        assert!((ratio_gb - 90.0 / 30.0).abs() < 0.1);
+        assert_eq!(data[3], 255);
+    }
+
+    #[test]
+    fn test_to_ascii_ramp_ends() {
+        let processor = ImageProcessor::new(4, 4);
+        let black = [0, 0, 0, 255].repeat(16);
+        let white = [255, 255, 255, 255].repeat(16);
+        
+        let black_art = processor.to_ascii(&black, 4);
+        let white_art = processor.to_ascii(&white, 4);
+        
+        // 4x4画像を4列にすると、文字の縦長さを考慮して2行になる
+        assert_eq!(black_art, "@@@@\n@@@@\n");
+        assert_eq!(white_art, "    \n    \n");
+    }
+
+    #[test]
+    fn test_gaussian_blur_with_alpha_softens_mask_edge() {
+        let mut processor = ImageProcessor::new(6, 1);
+        // 左半分が透明、右半分が不透明のマスク
+        let mut data = Vec::new();
+        for x in 0..6 {
+            let a = if x < 3 { 0 } else { 255 };
+            data.extend_from_slice(&[255, 255, 255, a]);
+        }
+        let mut alpha_preserved = data.clone();
+        
+        processor.gaussian_blur_with_alpha(&mut data, 1.0, true);
+        processor.gaussian_blur_with_alpha(&mut alpha_preserved, 1.0, false);
+        
+        // フラグ有効時は境界のAlphaがグラデーションになる
+        let alphas: Vec<u8> = data.chunks(4).map(|p| p[3]).collect();
+        assert_eq!(alphas[0], 0);
+        assert!(alphas[2] > 0 && alphas[2] < 255);
+        assert!(alphas[3] > alphas[2] && alphas[3] < 255);
+        assert_eq!(alphas[5], 255);
+        
+        // フラグ無効時はAlphaは変化しない
+        let hard: Vec<u8> = alpha_preserved.chunks(4).map(|p| p[3]).collect();
+        assert_eq!(hard, vec![0, 0, 0, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_invert_luma_keeps_hue() {
+        let mut processor = ImageProcessor::new(2, 1);
+        let mut data = vec![
+            200, 30, 30, 255,    // 暗めの赤
+            255, 255, 255, 255,  // 白
+        ];
+        
+        processor.invert_luma(&mut data);
+        
+        // 赤は赤みを保ったまま明るくなる
+        let before = processor.luma(200.0, 30.0, 30.0);
+        let after = processor.luma(data[0] as f32, data[1] as f32, data[2] as f32);
+        assert!(data[0] > data[1] && data[0] > data[2]);
+        assert!(after > before);
+        // 白は黒になる
+        assert_eq!(&data[4..8], &[0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_tone_curve_identity() {
+        let mut processor = ImageProcessor::new(10, 10);
+        let mut data = create_test_image_data(10, 10);
+        let original_data = data.clone();
+        
+        // 対角線のカーブは恒等変換
+        processor.try_tone_curve(&mut data, &[0.0, 0.0, 255.0, 255.0]).unwrap();
+        assert_eq!(data, original_data);
+        
+        // 昇順でない・範囲外の制御点はエラー
+        assert!(processor.try_tone_curve(&mut data, &[128.0, 0.0, 64.0, 255.0]).is_err());
+        assert!(processor.try_tone_curve(&mut data, &[0.0, 0.0, 300.0, 255.0]).is_err());
+        assert!(processor.try_tone_curve(&mut data, &[0.0, 0.0]).is_err());
+    }
+
+    #[test]
+    fn test_skin_smooth_leaves_non_skin_untouched() {
+        let mut processor = ImageProcessor::new(4, 4);
+        // ノイズを含む青い領域
+        let mut data = Vec::new();
+        for i in 0..16 {
+            let noise = (i % 3) as u8 * 10;
+            data.extend_from_slice(&[20 + noise, 40, 200 - noise, 255]);
+        }
+        let original_data = data.clone();
+        
+        processor.skin_smooth(&mut data, 1.0);
+        
+        // 肌色でない画素は変化しない
+        assert_eq!(data, original_data);
+    }
+
+    #[test]
+    fn test_skin_smooth_smooths_skin() {
+        let mut processor = ImageProcessor::new(4, 4);
+        // ノイズを含む肌色の領域
+        let mut data = Vec::new();
+        for i in 0..16 {
+            let noise = if i % 2 == 0 { 8 } else { 0 };
+            data.extend_from_slice(&[220 - noise, 170 - noise, 140 - noise, 255]);
+        }
+        let original_data = data.clone();
+        
+        processor.skin_smooth(&mut data, 1.0);
+        
+        assert_ne!(data, original_data);
+    }
+
+    #[test]
+    fn test_u32_fast_path_matches_byte_path() {
+        let mut processor = ImageProcessor::new(10, 10);
+        let mut data = create_test_image_data(10, 10);
+        let mut byte_data = data.clone();
+        
+        processor.negative(&mut data);
+        // バイト単位の参照実装
+        for i in (0..byte_data.len()).step_by(4) {
+            byte_data[i] = 255 - byte_data[i];
+            byte_data[i + 1] = 255 - byte_data[i + 1];
+            byte_data[i + 2] = 255 - byte_data[i + 2];
+        }
+        
+        assert_eq!(data, byte_data);
+        
+        // 長さが4の倍数でないデータはエラーとなり、変更されない
+        let mut partial = vec![1, 2, 3, 4, 5];
+        assert!(for_each_pixel_u32(&mut partial, |p| p ^ RGB_MASK).is_err());
+        assert_eq!(partial, vec![1, 2, 3, 4, 5]);
+        processor.negative(&mut partial);
+        assert_eq!(partial, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_extract_channel() {
+        let processor = ImageProcessor::new(2, 1);
+        let data = vec![
+            255, 0, 0, 255,  // 赤
+            0, 0, 255, 128,  // 半透明の青
+        ];
+        
+        // 赤チャンネルは純赤で最大輝度のグレーになる
+        let red = processor.try_extract_channel(&data, 0).unwrap();
+        assert_eq!(red, vec![255, 255, 255, 255, 0, 0, 0, 255]);
+        
+        let alpha = processor.try_extract_channel(&data, 3).unwrap();
+        assert_eq!(alpha, vec![255, 255, 255, 255, 128, 128, 128, 255]);
+        
+        assert!(processor.try_extract_channel(&data, 4).is_err());
+    }
+
+    #[test]
+    fn test_gradient_map_two_stops_is_duotone() {
+        let mut processor = ImageProcessor::new(10, 10);
+        let mut data = create_test_image_data(10, 10);
+        let original_data = data.clone();
+        let shadow = [20.0, 0.0, 80.0];
+        let highlight = [255.0, 200.0, 40.0];
+        
+        processor.try_gradient_map(&mut data, &[20, 0, 80, 255, 200, 40]).unwrap();
+        
+        // 2色の場合は影色とハイライト色の間を輝度で線形補間するデュオトーンと同じ
+        for i in (0..data.len()).step_by(4) {
+            let luma = processor.get_gray_value(&original_data, (i / 4) % 10, i / 40, 10) as f32;
+            for c in 0..3 {
+                let expected = shadow[c] + (highlight[c] - shadow[c]) * luma / 255.0;
+                assert!((data[i + c] as f32 - expected).abs() <= 1.0);
+            }
+            assert_eq!(data[i + 3], 255);
+        }
+        
+        assert!(processor.try_gradient_map(&mut data, &[0, 0, 0]).is_err());
+        assert!(processor.try_gradient_map(&mut data, &[0, 0, 0, 255]).is_err());
+    }
+
+    #[test]
+    fn test_despeckle_removes_outlier_and_keeps_edge() {
+        let mut processor = ImageProcessor::new(6, 5);
+        // 左半分が黒、右半分が白の画像
+        let mut data = Vec::new();
+        for _y in 0..5 {
+            for x in 0..6 {
+                let v = if x < 3 { 0 } else { 255 };
+                data.extend_from_slice(&[v, v, v, 255]);
+            }
+        }
+        let original_data = data.clone();
+        // 黒い領域に孤立した白い外れ値を追加
+        let outlier = (2 * 6 + 1) * 4;
+        data[outlier..outlier + 3].copy_from_slice(&[255, 255, 255]);
+        
+        processor.despeckle(&mut data, 30);
+        
+        // 外れ値は除去され、エッジはそのまま残る
+        assert_eq!(data, original_data);
+    }
+
+    #[test]
+    fn test_anisotropic_diffusion_smooths_noise_keeps_edge() {
+        let mut processor = ImageProcessor::new(8, 8);
+        // 左半分はノイズを含む暗い領域、右半分は白
+        let mut data = Vec::new();
+        for y in 0..8 {
+            for x in 0..8 {
+                let v = if x < 4 { 40 + ((x + y) % 2) as u8 * 10 } else { 255 };
+                data.extend_from_slice(&[v, v, v, 255]);
+            }
+        }
+        let noise_range = |d: &[u8]| {
+            let values: Vec<u8> = (0..8).flat_map(|y| (0..3).map(move |x| (y, x)))
+                .map(|(y, x)| d[(y * 8 + x) * 4])
+                .collect();
+            values.iter().max().unwrap() - values.iter().min().unwrap()
+        };
+        let before = noise_range(&data);
+        
+        processor.anisotropic_diffusion(&mut data, 10, 20.0);
+        
+        // ノイズは平滑化される
+        assert!(noise_range(&data) < before);
+        // 強いエッジはシャープなまま
+        let left = data[(3 * 8 + 3) * 4] as i32;
+        let right = data[(3 * 8 + 4) * 4] as i32;
+        assert!(right - left > 180);
+    }
+
+    #[test]
+    fn test_polar_transform() {
+        let mut processor = ImageProcessor::new(9, 9);
+        let mut data = create_test_image_data(9, 9);
+        let original_data = data.clone();
+        
+        processor.polar_transform(&mut data);
+        
+        // データ長は保持され、グラデーションは変換される
+        assert_eq!(data.len(), original_data.len());
+        assert_ne!(data, original_data);
+        // 中心は元画像の下端の色になる
+        let center = (4 * 9 + 4) * 4;
+        assert_eq!(data[center + 1], original_data[(8 * 9) * 4 + 1]);
+    }
+
+    #[test]
+    fn test_parallel_matches_serial() {
+        let processor = ImageProcessor::new(37, 23);
+        let data = create_test_image_data(37, 23);
+        let (width, height) = (37, 23);
+        
+        // 並列版 (for_each_row) のブラー
+        let mut parallel = data.clone();
+        processor.blur_horizontal_by_row(&mut parallel, width, height, 3, |_| 2.0);
+        processor.blur_vertical_by_row(&mut parallel, width, height, 3, |_| 2.0);
+        
+        // 同じ行処理を逐次で実行
+        let mut serial = data.clone();
+        let temp = serial.clone();
+        for_each_row_serial(&mut serial, width * 4, |y, row| {
+            processor.blur_row_horizontal(&temp, row, y, width, 3, 2.0);
+        });
+        let column_sums = column_prefix_sums(&serial, width, height, 3);
+        for_each_row_serial(&mut serial, width * 4, |y, row| {
+            processor.blur_row_vertical(&column_sums, row, y, width, height, 3, 2.0);
+        });
+        
+        assert_eq!(parallel, serial);
+        
+        // 画素単位の処理も逐次の参照実装と一致
+        let mut parallel = data.clone();
+        for_each_pixel_u32(&mut parallel, |p| p ^ RGB_MASK).unwrap();
+        let serial: Vec<u8> = data.iter().enumerate()
+            .map(|(i, &v)| if i % 4 == 3 { v } else { 255 - v })
+            .collect();
+        assert_eq!(parallel, serial);
+        
+        let mut parallel = data.clone();
+        for_each_pixel(&mut parallel, |pixel| pixel[..3].iter_mut().for_each(|v| *v = 255 - *v));
+        assert_eq!(parallel, serial);
+    }
+
+    #[test]
+    fn test_process_tiled_matches_whole_image() {
+        let filters = [Filter::GaussianBlur, Filter::EdgeDetection, Filter::HdrAnime, Filter::Negative];
+        for filter in filters {
+            let mut processor = ImageProcessor::new(23, 17);
+            let mut tiled = create_test_image_data(23, 17);
+            // 継ぎ目で差が出やすいよう高周波成分を加える
+            for (i, v) in tiled.iter_mut().enumerate() {
+                if i % 4 != 3 && (i / 4) % 3 == 0 {
+                    *v = v.wrapping_add(90);
+                }
+            }
+            let mut whole = tiled.clone();
+            
+            processor.process_tiled(&mut tiled, filter, 8);
+            processor.apply(&mut whole, filter);
+            
+            // タイルの継ぎ目を含め全画素で±1以内
+            for (a, b) in tiled.iter().zip(whole.iter()) {
+                assert!((*a as i32 - *b as i32).abs() <= 1, "{:?}", filter);
+            }
+        }
+    }
+
+    #[test]
+    fn test_map_pixels_identity() {
+        let mut processor = ImageProcessor::new(4, 4);
+        let mut data = create_test_image_data(4, 4);
+        let original_data = data.clone();
+        
+        // 恒等変換では画像は変化しない
+        processor.try_map_pixels_with(&mut data, Ok::<_, ()>).unwrap();
+        assert_eq!(data, original_data);
+        
+        // エラー時はその画素以降を処理しない
+        let mut count = 0;
+        let result = processor.try_map_pixels_with(&mut data, |_| {
+            count += 1;
+            if count == 3 { Err("stop") } else { Ok([0, 0, 0, 0]) }
+        });
+        assert_eq!(result, Err("stop"));
+        assert_eq!(&data[0..8], &[0; 8]);
+        assert_eq!(&data[8..], &original_data[8..]);
+    }
+
+    #[test]
+    fn test_emboss_directional_flips_with_angle() {
+        let mut processor = ImageProcessor::new(6, 5);
+        // 左半分が暗く、右半分が明るい画像
+        let mut data = Vec::new();
+        for _y in 0..5 {
+            for x in 0..6 {
+                let v = if x < 3 { 50 } else { 200 };
+                data.extend_from_slice(&[v, v, v, 255]);
+            }
+        }
+        let mut opposite = data.clone();
+        
+        processor.emboss_directional(&mut data, 0.0, 1.0);
+        processor.emboss_directional(&mut opposite, 180.0, 1.0);
+        
+        // 光源を180度回転するとエッジのハイライトと影が入れ替わる
+        let edge = (2 * 6 + 2) * 4;
+        assert!(data[edge] > 128);
+        assert!(opposite[edge] < 128);
+        // 平坦な領域は中間グレー
+        let flat = (2 * 6 + 4) * 4;
+        assert_eq!(data[flat], 128);
+        assert_eq!(data[flat + 3], 255);
+    }
+
+    #[test]
+    fn test_overlay_image_blends_and_clips() {
+        let mut processor = ImageProcessor::new(2, 2);
+        let mut base = [0, 0, 200, 255].repeat(4);
+        
+        // 半透明の赤を (1, 1) に合成
+        processor.try_overlay_image(&mut base, &[255, 0, 0, 128], 1, 1, 1, 1).unwrap();
+        assert_eq!(&base[12..16], &[128, 0, 100, 255]);
+        // 他の画素は変化しない
+        assert_eq!(&base[0..4], &[0, 0, 200, 255]);
+        
+        // はみ出した部分は切り捨てる
+        let overlay = [255, 255, 255, 255].repeat(4);
+        processor.try_overlay_image(&mut base, &overlay, 2, 2, 1, 1).unwrap();
+        assert_eq!(&base[12..16], &[255, 255, 255, 255]);
+        assert_eq!(&base[0..4], &[0, 0, 200, 255]);
+        
+        // データ長が不正な場合はエラー
+        assert!(processor.try_overlay_image(&mut base, &[0, 0, 0], 1, 1, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_sharpen_luma_keeps_chroma() {
+        let mut processor = ImageProcessor::new(6, 3);
+        // 暗い赤と明るい赤の境界
+        let mut data = Vec::new();
+        for _y in 0..3 {
+            for x in 0..6 {
+                let pixel: [u8; 4] = if x < 3 { [120, 40, 40, 255] } else { [200, 120, 120, 255] };
+                data.extend_from_slice(&pixel);
+            }
+        }
+        let original_data = data.clone();
+        
+        processor.sharpen_luma(&mut data, 0.2);
+        
+        // 境界のコントラストが強くなる
+        let dark = (6 + 2) * 4;
+        let light = (6 + 3) * 4;
+        assert!(data[light] as i32 - data[dark] as i32 > original_data[light] as i32 - original_data[dark] as i32);
+        // 各画素の色差 (R-G, R-B) は変化しない
+        for i in (0..data.len()).step_by(4) {
+            assert_eq!(data[i] as i32 - data[i + 1] as i32, original_data[i] as i32 - original_data[i + 1] as i32);
+            assert_eq!(data[i] as i32 - data[i + 2] as i32, original_data[i] as i32 - original_data[i + 2] as i32);
+        }
+    }
+
+    #[test]
+    fn test_process_to_bgra_swaps_red_and_blue() {
+        let mut processor = ImageProcessor::new(4, 4);
+        let data = create_test_image_data(4, 4);
+        let mut rgba = data.clone();
+        
+        let bgra = processor.process_to_bgra(&data, Filter::SepiaTone);
+        processor.sepia_tone(&mut rgba);
+        
+        // RとBが入れ替わり、GとAはそのまま
+        for i in (0..data.len()).step_by(4) {
+            assert_eq!(bgra[i], rgba[i + 2]);
+            assert_eq!(bgra[i + 1], rgba[i + 1]);
+            assert_eq!(bgra[i + 2], rgba[i]);
+            assert_eq!(bgra[i + 3], rgba[i + 3]);
+        }
+        
+        // 近傍を参照するフィルタも同じく入れ替わる
+        let bgra = processor.process_to_bgra(&data, Filter::GaussianBlur);
+        let mut rgba = data.clone();
+        processor.apply(&mut rgba, Filter::GaussianBlur);
+        for (bgra_pixel, rgba_pixel) in bgra.chunks_exact(4).zip(rgba.chunks_exact(4)) {
+            assert_eq!(bgra_pixel, [rgba_pixel[2], rgba_pixel[1], rgba_pixel[0], rgba_pixel[3]]);
+        }
+    }
+
+    #[test]
+    fn test_blur_by_luminance_keeps_out_of_range_pixels() {
+        let mut processor = ImageProcessor::new(8, 8);
+        let mut data = create_test_image_data(8, 8);
+        let original_data = data.clone();
+        
+        processor.blur_by_luminance(&mut data, 2.0, 0, 80);
+        
+        // 範囲外の画素はバイト単位で変化しない
+        let mut blurred_count = 0;
+        for i in (0..data.len()).step_by(4) {
+            let luma = processor.luma(original_data[i] as f32, original_data[i + 1] as f32, original_data[i + 2] as f32);
+            if luma as u8 > 80 {
+                assert_eq!(&data[i..i + 4], &original_data[i..i + 4]);
+            } else if data[i..i + 4] != original_data[i..i + 4] {
+                blurred_count += 1;
+            }
+        }
+        assert!(blurred_count > 0);
+    }
+
+    #[test]
+    fn test_composite_over_checkerboard() {
+        let processor = ImageProcessor::new(2, 1);
+        let data = vec![
+            255, 0, 0, 0,    // 完全に透明
+            10, 20, 30, 255, // 不透明
+        ];
+        
+        let output = processor.composite_over_checkerboard(&data, 1);
+        
+        // 透明な画素はチェッカーボードの色、不透明な画素は元の色
+        assert_eq!(&output[0..4], &[CHECKER_LIGHT, CHECKER_LIGHT, CHECKER_LIGHT, 255]);
+        assert_eq!(&output[4..8], &[10, 20, 30, 255]);
+    }
+
+    #[test]
+    fn test_mse_and_ssim() {
+        let processor = ImageProcessor::new(8, 8);
+        let a = create_test_image_data(8, 8);
+        // 1画素ずらした画像
+        let mut shifted = a[4..].to_vec();
+        shifted.extend_from_slice(&a[..4]);
+        
+        assert_eq!(processor.try_mse(&a, &a).unwrap(), 0.0);
+        assert!(processor.try_mse(&a, &shifted).unwrap() > 0.0);
+        
+        assert!((processor.try_ssim(&a, &a).unwrap() - 1.0).abs() < 1e-9);
+        assert!(processor.try_ssim(&a, &shifted).unwrap() < 1.0);
+        
+        // データ長が異なる場合はエラー
+        assert!(processor.try_mse(&a, &a[..4]).is_err());
+        assert!(processor.try_ssim(&a, &a[..4]).is_err());
+    }
+
+    #[test]
+    fn test_dither_ordered() {
+        let mut processor = ImageProcessor::new(8, 8);
+        let mut data = create_test_image_data(8, 8);
+        let mut again = data.clone();
+        
+        // 同じ入力からは同じ出力
+        processor.try_dither_ordered(&mut data, 2, 4).unwrap();
+        processor.try_dither_ordered(&mut again, 2, 4).unwrap();
+        assert_eq!(data, again);
+        assert!(data.iter().all(|&v| v == 0 || v == 255));
+        
+        // 中間グレーは2x2行列で市松模様になる
+        let mut gray = [128, 128, 128, 255].repeat(64);
+        processor.try_dither_ordered(&mut gray, 2, 2).unwrap();
+        for y in 0..8 {
+            for x in 0..8 {
+                let expected = if (x + y) % 2 == 0 { 0 } else { 255 };
+                assert_eq!(gray[(y * 8 + x) * 4], expected);
+            }
+        }
+        
+        assert!(processor.try_dither_ordered(&mut gray, 1, 2).is_err());
+        assert!(processor.try_dither_ordered(&mut gray, 2, 3).is_err());
+        assert_eq!(bayer_matrix(2), vec![0, 2, 3, 1]);
+    }
+
+    #[test]
+    fn test_kaleidoscope() {
+        let mut processor = ImageProcessor::new(9, 9);
+        let mut data = create_test_image_data(9, 9);
+        let original_data = data.clone();
+        
+        // segments==1 は変化なし
+        processor.kaleidoscope(&mut data, 1);
+        assert_eq!(data, original_data);
+        
+        // 2分割では上下が鏡映になる
+        processor.kaleidoscope(&mut data, 2);
+        assert_eq!(data.len(), original_data.len());
+        for x in 0..9 {
+            let top = x * 4;
+            let bottom = (8 * 9 + x) * 4;
+            for c in 0..3 {
+                assert!((data[top + c] as i32 - data[bottom + c] as i32).abs() <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotate() {
+        let processor = ImageProcessor::new(8, 8);
+        let data = create_test_image_data(8, 8);
+        let bg = [0, 0, 0, 0];
+        
+        // 0度は恒等変換
+        assert_eq!(processor.try_rotate(&data, 0.0, &bg).unwrap(), data);
+        
+        // 360度はほぼ元の画像
+        let full_turn = processor.try_rotate(&data, 360.0, &bg).unwrap();
+        for (a, b) in full_turn.iter().zip(data.iter()) {
+            assert!((*a as i32 - *b as i32).abs() <= 1);
+        }
+        
+        // 45度回転すると角は背景色になる
+        let rotated = processor.try_rotate(&data, 45.0, &bg).unwrap();
+        assert_eq!(&rotated[0..4], &bg);
+        
+        assert!(processor.try_rotate(&data, 0.0, &[0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_luma_key() {
+        let mut processor = ImageProcessor::new(3, 1);
+        let mut data = vec![
+            5, 5, 5, 255,        // ほぼ黒
+            128, 128, 128, 255,  // 中間グレー
+            250, 250, 250, 255,  // ほぼ白
+        ];
+        
+        processor.luma_key(&mut data, 16, 240);
+        
+        assert_eq!(data[3], 0);
+        assert_eq!(data[7], 255);
+        assert_eq!(data[11], 0);
+        // RGBは変化しない
+        assert_eq!(&data[4..7], &[128, 128, 128]);
+    }
+
+    /// 2次元カーネルによるSobel X方向の参照実装
+    fn reference_sobel_x(processor: &ImageProcessor, data: &[u8], x: usize, y: usize, width: usize) -> i32 {
+        let kernel = [-1, 0, 1, -2, 0, 2, -1, 0, 1];
+        let mut sum = 0;
+        for ky in 0..3 {
+            for kx in 0..3 {
+                let gray = processor.get_gray_value(data, x + kx - 1, y + ky - 1, width);
+                sum += gray as i32 * kernel[ky * 3 + kx];
+            }
+        }
+        sum
+    }
+
+    /// 2次元カーネルによるSobel Y方向の参照実装
+    fn reference_sobel_y(processor: &ImageProcessor, data: &[u8], x: usize, y: usize, width: usize) -> i32 {
+        let kernel = [-1, -2, -1, 0, 0, 0, 1, 2, 1];
+        let mut sum = 0;
+        for ky in 0..3 {
+            for kx in 0..3 {
+                let gray = processor.get_gray_value(data, x + kx - 1, y + ky - 1, width);
+                sum += gray as i32 * kernel[ky * 3 + kx];
+            }
+        }
+        sum
+    }
+
+    #[test]
+    fn test_separable_sobel_matches_2d() {
+        let processor = ImageProcessor::new(11, 9);
+        let mut data = create_test_image_data(11, 9);
+        for (i, v) in data.iter_mut().enumerate() {
+            *v = v.wrapping_mul(7).wrapping_add((i * 13) as u8);
+        }
+        
+        let gray = processor.gray_buffer(&data);
+        let (gradient_x, gradient_y) = processor.sobel_gradients(&gray, 11, 9);
+        
+        // 分離型の結果は2次元カーネルの結果と完全に一致
+        for y in 1..8 {
+            for x in 1..10 {
+                assert_eq!(gradient_x[y * 11 + x], reference_sobel_x(&processor, &data, x, y, 11));
+                assert_eq!(gradient_y[y * 11 + x], reference_sobel_y(&processor, &data, x, y, 11));
+            }
+        }
+    }
+
+    #[test]
+    fn test_edge_detection_matches_per_pixel_reference() {
+        let mut processor = ImageProcessor::new(12, 10);
+        let mut data = create_test_image_data(12, 10);
+        for (i, v) in data.iter_mut().enumerate() {
+            if i % 4 != 3 {
+                *v = v.wrapping_mul(5).wrapping_add((i * 31) as u8);
+            }
+        }
+        
+        // 画素ごとに近傍の輝度を再計算する従来の実装
+        let mut expected = data.clone();
+        for y in 1..9 {
+            for x in 1..11 {
+                let gx = reference_sobel_x(&processor, &data, x, y, 12);
+                let gy = reference_sobel_y(&processor, &data, x, y, 12);
+                let magnitude = ((gx * gx + gy * gy) as f32).sqrt() as u8;
+                let idx = (y * 12 + x) * 4;
+                expected[idx..idx + 3].copy_from_slice(&[magnitude; 3]);
+            }
+        }
+        
+        // グレースケールを事前計算した実装と出力が一致
+        processor.edge_detection(&mut data);
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn test_glitch_deterministic() {
+        let mut processor = ImageProcessor::new(16, 16);
+        let original_data = create_test_image_data(16, 16);
+        
+        // 同じシードでは同じ出力
+        let mut first = original_data.clone();
+        let mut second = original_data.clone();
+        processor.glitch(&mut first, 0.8, 42);
+        processor.glitch(&mut second, 0.8, 42);
+        assert_eq!(first, second);
+        assert!(first != original_data);
+        
+        // intensity 0.0 は変化なし
+        let mut unchanged = original_data.clone();
+        processor.glitch(&mut unchanged, 0.0, 42);
+        assert_eq!(unchanged, original_data);
+    }
+
+    #[test]
+    fn test_edge_enhance_strength() {
+        let mut processor = ImageProcessor::new(8, 8);
+        // 左半分が暗く右半分が明るい縦エッジ
+        let mut original_data = vec![0u8; 8 * 8 * 4];
+        for y in 0..8 {
+            for x in 0..8 {
+                let idx = (y * 8 + x) * 4;
+                let value = if x < 4 { 60 } else { 120 };
+                original_data[idx..idx + 4].copy_from_slice(&[value, value, value, 255]);
+            }
+        }
+        
+        // strength 0.0 は変化なし
+        let mut unchanged = original_data.clone();
+        processor.edge_enhance(&mut unchanged, 0.0);
+        assert_eq!(unchanged, original_data);
+        
+        // 強さを上げるとエッジ上のピクセルが明るくなる
+        let mut weak = original_data.clone();
+        let mut strong = original_data.clone();
+        processor.edge_enhance(&mut weak, 0.5);
+        processor.edge_enhance(&mut strong, 1.0);
+        let edge_idx = (4 * 8 + 4) * 4;
+        assert!(weak[edge_idx] > original_data[edge_idx]);
+        assert!(strong[edge_idx] > weak[edge_idx]);
+    }
+
+    #[test]
+    fn test_dominant_colors_two_colors() {
+        let processor = ImageProcessor::new(4, 4);
+        // 赤12ピクセルと青4ピクセルの画像
+        let mut data = Vec::new();
+        for i in 0..16 {
+            if i < 12 {
+                data.extend_from_slice(&[220, 30, 40, 255]);
+            } else {
+                data.extend_from_slice(&[10, 50, 200, 255]);
+            }
+        }
+        
+        let colors = processor.dominant_colors(&data, 2);
+        
+        // 多い色から順に返る
+        assert_eq!(colors, vec![220, 30, 40, 10, 50, 200]);
+    }
+
+    #[test]
+    fn test_quantize_to_palette() {
+        let mut processor = ImageProcessor::new(4, 4);
+        let palette = [0, 0, 0, 255, 255, 255, 255, 0, 0];
+        let mut data = create_test_image_data(4, 4);
+        
+        processor.try_quantize_to_palette(&mut data, &palette).unwrap();
+        
+        // すべてのピクセルがパレットのいずれかの色と完全一致する
+        for pixel in data.chunks_exact(4) {
+            assert!(palette.chunks_exact(3).any(|entry| entry == &pixel[..3]));
+            assert_eq!(pixel[3], 255);
+        }
+        
+        // 長さが3の倍数でないパレットはエラー
+        assert!(processor.try_quantize_to_palette(&mut data, &[0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_sepia_hue_depth() {
+        let mut processor = ImageProcessor::new(4, 4);
+        let original_data = create_test_image_data(4, 4);
+        
+        // depth 0.0 は純粋なグレースケール
+        let mut gray = original_data.clone();
+        processor.sepia_hue(&mut gray, 0.0);
+        for pixel in gray.chunks_exact(4) {
+            assert_eq!(pixel[0], pixel[1]);
+            assert_eq!(pixel[1], pixel[2]);
+        }
+        
+        // depth 1.0 では暖色 (R > G > B) になる
+        let mut sepia = vec![128, 128, 128, 255];
+        processor.sepia_hue(&mut sepia, 1.0);
+        assert!(sepia[0] > sepia[1] && sepia[1] > sepia[2]);
+        assert_eq!(sepia[3], 255);
+    }
+
+    #[test]
+    fn test_blur_with_mask_zero_is_sharp() {
+        let mut processor = ImageProcessor::new(8, 8);
+        let original_data = create_test_image_data(8, 8);
+        
+        // 左半分はマスク0 (シャープ)、右半分は255 (最大ブラー)
+        let mask: Vec<u8> = (0..64).map(|i| if i % 8 < 4 { 0 } else { 255 }).collect();
+        let mut data = original_data.clone();
+        processor.try_blur_with_mask(&mut data, &mask, 2.0).unwrap();
+        
+        for (i, &m) in mask.iter().enumerate() {
+            if m == 0 {
+                assert_eq!(data[i * 4..i * 4 + 4], original_data[i * 4..i * 4 + 4]);
+            }
+        }
+        assert!(data != original_data);
+        
+        // マスクの長さが一致しない場合はエラー
+        assert!(processor.try_blur_with_mask(&mut data, &[0; 10], 2.0).is_err());
+        // 画像データが短い場合もパニックせずエラー
+        assert!(processor.try_blur_with_mask(&mut data[..100], &mask, 2.0).is_err());
+    }
+
+    #[test]
+    fn test_blur_with_mask_matches_per_radius_blur() {
+        let mut processor = ImageProcessor::new(8, 8);
+        let original_data = create_test_image_data(8, 8);
+        
+        // マスク255は最大半径のブラー、中間値は隣り合う段階の補間
+        let mut data = original_data.clone();
+        processor.try_blur_with_mask(&mut data, &[255; 64], 3.0).unwrap();
+        let mut blurred = original_data.clone();
+        processor.gaussian_blur(&mut blurred, 3.0);
+        assert_eq!(data, blurred);
+        
+        let mut data = original_data.clone();
+        processor.try_blur_with_mask(&mut data, &[170; 64], 3.0).unwrap();
+        let mut blurred = original_data.clone();
+        processor.gaussian_blur(&mut blurred, 2.0);
+        assert_eq!(data, blurred);
+        
+        // 上限を超える半径は上限で打ち切られる
+        let mut huge = original_data.clone();
+        processor.try_blur_with_mask(&mut huge, &[255; 64], 10000.0).unwrap();
+        let mut capped = original_data.clone();
+        processor.try_blur_with_mask(&mut capped, &[255; 64], 8.0).unwrap();
+        assert_eq!(huge, capped);
+    }
+
+    #[test]
+    fn test_auto_levels_per_channel() {
+        let mut processor = ImageProcessor::new(4, 4);
+        // Rは100..150、Gは全範囲、Bは一定値
+        let mut data = Vec::new();
+        for i in 0..16u32 {
+            let r = 100 + (i * 50 / 15) as u8;
+            let g = (i * 255 / 15) as u8;
+            data.extend_from_slice(&[r, g, 80, 255]);
+        }
+        let original_data = data.clone();
+        
+        processor.auto_levels_per_channel(&mut data);
+        
+        let reds: Vec<u8> = data.chunks_exact(4).map(|p| p[0]).collect();
+        assert_eq!(*reds.iter().min().unwrap(), 0);
+        assert_eq!(*reds.iter().max().unwrap(), 255);
+        
+        // 既に全範囲のチャンネルと一定値のチャンネルは変化なし
+        for (pixel, original) in data.chunks_exact(4).zip(original_data.chunks_exact(4)) {
+            assert_eq!(pixel[1..], original[1..]);
+        }
+    }
+
+    /// 3x2のRGBA PNG (上段: 赤・緑・青、下段: 白・黒・半透明グレー)
+    #[cfg(feature = "image")]
+    const TINY_PNG: &[u8] = &[
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+        0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0x08, 0x06, 0x00, 0x00, 0x00, 0x9d, 0x74, 0x66,
+        0x1a, 0x00, 0x00, 0x00, 0x19, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0xf8, 0xcf, 0xc0, 0xf0,
+        0x1f, 0x0c, 0x19, 0xfe, 0x03, 0x49, 0x20, 0x60, 0x00, 0xb2, 0x1a, 0x80, 0x00, 0x00, 0xa5, 0x79,
+        0x0c, 0xf6, 0x6e, 0xfc, 0x35, 0x9e, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42,
+        0x60, 0x82,
+    ];
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_decode_image_png() {
+        let decoded = try_decode_image(TINY_PNG).unwrap();
+        
+        assert_eq!(decoded.width(), 3);
+        assert_eq!(decoded.height(), 2);
+        assert_eq!(decoded.data().len(), 3 * 2 * 4);
+        assert_eq!(decoded.data()[0..4], [255, 0, 0, 255]);
+        assert_eq!(decoded.data()[20..24], [128, 128, 128, 128]);
+        
+        // 壊れたデータはエラー
+        assert!(try_decode_image(&TINY_PNG[..20]).is_err());
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_encode_png_round_trip() {
+        let processor = ImageProcessor::new(5, 3);
+        let data = create_test_image_data(5, 3);
+        
+        let png = processor.try_encode_png(&data).unwrap();
+        let decoded = try_decode_image(&png).unwrap();
+        
+        assert_eq!(decoded.width(), 5);
+        assert_eq!(decoded.height(), 3);
+        assert_eq!(decoded.data(), data);
+        
+        // サイズが一致しないバッファはエラー
+        assert!(processor.try_encode_png(&data[..8]).is_err());
+    }
+
+    #[test]
+    fn test_vibrance_boosts_muted_colors_more() {
+        let mut processor = ImageProcessor::new(2, 1);
+        // 彩度の低い青みがかったグレーと、彩度の高い青
+        let mut data = vec![110, 120, 140, 255, 20, 40, 230, 255];
+        let (_, muted_before, _) = rgb_to_hsv(110, 120, 140);
+        let (_, vivid_before, _) = rgb_to_hsv(20, 40, 230);
+        
+        processor.vibrance(&mut data, 1.0);
+        
+        let (_, muted_after, _) = rgb_to_hsv(data[0], data[1], data[2]);
+        let (_, vivid_after, _) = rgb_to_hsv(data[4], data[5], data[6]);
+        assert!(muted_after / muted_before > vivid_after / vivid_before);
+        assert!(muted_after > muted_before);
+    }
+
+    #[test]
+    fn test_comic_outlines_and_posterizes() {
+        let mut processor = ImageProcessor::new(8, 8);
+        // 左半分が暗く右半分が明るい縦エッジ
+        let mut data = vec![0u8; 8 * 8 * 4];
+        for y in 0..8 {
+            for x in 0..8 {
+                let idx = (y * 8 + x) * 4;
+                let value = if x < 4 { 70 } else { 190 };
+                data[idx..idx + 4].copy_from_slice(&[value, value, value, 255]);
+            }
+        }
+        
+        processor.try_comic(&mut data, 2, 100).unwrap();
+        
+        // エッジ上の画素は黒になる
+        let edge_idx = (4 * 8 + 4) * 4;
+        assert_eq!(data[edge_idx..edge_idx + 4], [0, 0, 0, 255]);
+        
+        // 平坦な領域は2階調 (0 または 255) に量子化される
+        let flat_dark = (4 * 8 + 1) * 4;
+        let flat_bright = (4 * 8 + 6) * 4;
+        assert_eq!(data[flat_dark..flat_dark + 3], [0, 0, 0]);
+        assert_eq!(data[flat_bright..flat_bright + 3], [255, 255, 255]);
+        
+        assert!(processor.try_comic(&mut data, 1, 100).is_err());
+    }
+
+    #[test]
+    fn test_symmetry_mirrors_left_onto_right() {
+        let mut processor = ImageProcessor::new(2, 3);
+        let original_data = create_test_image_data(2, 3);
+        
+        let mut data = original_data.clone();
+        processor.try_symmetry(&mut data, 0).unwrap();
+        for y in 0..3 {
+            let left = y * 2 * 4;
+            assert_eq!(data[left + 4..left + 8], original_data[left..left + 4]);
+            assert_eq!(data[left..left + 4], original_data[left..left + 4]);
+        }
+        
+        // 上下対称では最終行が先頭行のコピーになる
+        let mut data = original_data.clone();
+        processor.try_symmetry(&mut data, 1).unwrap();
+        assert_eq!(data[16..24], original_data[0..8]);
+        assert_eq!(data[8..16], original_data[8..16]);
+        
+        assert!(processor.try_symmetry(&mut data, 2).is_err());
+    }
+
+    #[test]
+    fn test_enhance_detail_smooths_noise_keeps_edges() {
+        let mut processor = ImageProcessor::new(8, 8);
+        // 左半分が暗く右半分が明るい縦エッジ + 暗い領域に1画素のノイズ
+        let mut data = vec![0u8; 8 * 8 * 4];
+        for y in 0..8 {
+            for x in 0..8 {
+                let idx = (y * 8 + x) * 4;
+                let value = if x < 4 { 60 } else { 180 };
+                data[idx..idx + 4].copy_from_slice(&[value, value, value, 255]);
+            }
+        }
+        let noise_idx = (2 * 8 + 1) * 4;
+        data[noise_idx..noise_idx + 3].fill(250);
+        let original_len = data.len();
+        
+        processor.enhance_detail(&mut data);
+        
+        assert_eq!(data.len(), original_len);
+        // ノイズは除去される
+        assert_eq!(data[noise_idx], 60);
+        // エッジのコントラストは保たれる (むしろ強調される)
+        let dark_edge = (5 * 8 + 3) * 4;
+        let bright_edge = (5 * 8 + 4) * 4;
+        assert!(data[dark_edge] <= 60);
+        assert!(data[bright_edge] >= 180);
+    }
+
+    #[test]
+    fn test_local_exposure_lifts_dark_regions() {
+        let mut processor = ImageProcessor::new(32, 32);
+        // 上半分が暗く下半分が明るい画像
+        let mut data = vec![0u8; 32 * 32 * 4];
+        for y in 0..32 {
+            for x in 0..32 {
+                let idx = (y * 32 + x) * 4;
+                let value = if y < 16 { 40 } else { 220 };
+                data[idx..idx + 4].copy_from_slice(&[value, value, value, 255]);
+            }
+        }
+        
+        processor.local_exposure(&mut data, 0.5);
+        
+        let dark = data[(2 * 32 + 10) * 4];
+        let bright = data[(29 * 32 + 10) * 4];
+        assert!(dark > 40);
+        assert!(bright < 220);
+        assert_eq!(data[3], 255);
+    }
+
+    #[test]
+    fn test_get_set_pixel() {
+        let processor = ImageProcessor::new(3, 2);
+        let mut data = create_test_image_data(3, 2);
+        
+        // 範囲内の読み書き
+        processor.try_set_pixel(&mut data, 2, 1, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(processor.try_get_pixel(&data, 2, 1).unwrap(), vec![1, 2, 3, 4]);
+        assert_eq!(data[(3 + 2) * 4..(3 + 2) * 4 + 4], [1, 2, 3, 4]);
+        assert_eq!(processor.try_get_pixel(&data, 0, 0).unwrap(), data[0..4].to_vec());
+        
+        // 範囲外の座標はエラー
+        assert!(processor.try_get_pixel(&data, 3, 0).is_err());
+        assert!(processor.try_get_pixel(&data, 0, 2).is_err());
+        assert!(processor.try_set_pixel(&mut data, 3, 1, &[0, 0, 0, 0]).is_err());
+        
+        // 4バイトでない色、短すぎるデータはエラー
+        assert!(processor.try_set_pixel(&mut data, 0, 0, &[0, 0, 0]).is_err());
+        assert!(processor.try_get_pixel(&data[..8], 2, 1).is_err());
+    }
+
+    #[test]
+    fn test_thermal_palette_ends() {
+        let mut processor = ImageProcessor::new(2, 1);
+        let mut data = vec![0, 0, 0, 255, 255, 255, 255, 255];
+        
+        processor.thermal(&mut data);
+        
+        // 黒は冷たい端 (青)、白は熱い端 (赤)
+        assert_eq!(data[0..4], [0, 0, 255, 255]);
+        assert_eq!(data[4..8], [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_frame_difference() {
+        let processor = ImageProcessor::new(4, 4);
+        let prev = create_test_image_data(4, 4);
+        
+        // 同一フレームはすべて黒
+        let mut curr = prev.clone();
+        processor.try_frame_difference(&prev, &mut curr, 10).unwrap();
+        for pixel in curr.chunks_exact(4) {
+            assert_eq!(pixel, [0, 0, 0, 255]);
+        }
+        
+        // 閾値を超えて変化した画素のみ残る
+        let mut curr = prev.clone();
+        curr[0] = prev[0].wrapping_add(100);
+        curr[4] = prev[4].wrapping_add(5);
+        let moved = curr[0..4].to_vec();
+        processor.try_frame_difference(&prev, &mut curr, 10).unwrap();
+        assert_eq!(curr[0..4], moved[..]);
+        assert_eq!(curr[4..8], [0, 0, 0, 255]);
+        
+        assert!(processor.try_frame_difference(&prev, &mut curr[..8], 10).is_err());
+    }
+
+    #[test]
+    fn test_denoise_temporal_pulls_toward_previous() {
+        let mut processor = ImageProcessor::new(2, 2);
+        let stable = vec![100u8; 2 * 2 * 4];
+        
+        // 初回は保持するだけで変化なし
+        let mut first = stable.clone();
+        processor.denoise_temporal(&mut first, 0.8);
+        assert_eq!(first, stable);
+        
+        // ノイズの乗ったフレームは保持したフレームに引き寄せられる
+        let mut noisy = stable.clone();
+        noisy[0] = 200;
+        processor.denoise_temporal(&mut noisy, 0.8);
+        assert_eq!(noisy[0], 120);
+        assert_eq!(noisy[1], 100);
+        
+        // リセット後は再び変化なし
+        processor.reset_previous_frame();
+        let mut after_reset = stable.clone();
+        after_reset[0] = 200;
+        processor.denoise_temporal(&mut after_reset, 0.8);
+        assert_eq!(after_reset[0], 200);
+    }
+
+    #[test]
+    fn test_thumbnail_dimensions_and_average() {
+        let processor = ImageProcessor::new(4, 2);
+        // 左半分が黒、右半分が白
+        let mut data = Vec::new();
+        for _ in 0..2 {
+            data.extend_from_slice(&[0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255, 255, 255, 255, 255, 255]);
+        }
+        
+        assert_eq!(processor.thumbnail_dimensions(2), vec![2, 1]);
+        let thumb = processor.thumbnail(&data, 2);
+        
+        assert_eq!(thumb.len(), 2 * 4);
+        assert_eq!(thumb, vec![0, 0, 0, 255, 255, 255, 255, 255]);
+        
+        // 元画像より大きい指定では拡大しない
+        assert_eq!(processor.thumbnail_dimensions(100), vec![4, 2]);
+    }
+
+    #[test]
+    fn test_gaussian_blur_huge_radius_is_clamped() {
+        let mut processor = ImageProcessor::new(16, 16);
+        let original_data = create_test_image_data(16, 16);
+        
+        // 極端に大きい半径でもすぐに終わり、画像サイズで打ち切った場合と同じ結果になる
+        let mut huge = original_data.clone();
+        let mut clamped = original_data.clone();
+        processor.gaussian_blur(&mut huge, 10000.0);
+        processor.gaussian_blur(&mut clamped, 16.0);
+        
+        assert_eq!(huge.len(), original_data.len());
+        assert_eq!(huge, clamped);
+        
+        let mut huge = original_data.clone();
+        let mut clamped = original_data.clone();
+        processor.gaussian_blur_linear(&mut huge, 10000.0);
+        processor.gaussian_blur_linear(&mut clamped, 16.0);
+        assert_eq!(huge, clamped);
+    }
+
+    #[test]
+    fn test_gaussian_blur_xy_horizontal_only() {
+        let mut processor = ImageProcessor::new(8, 8);
+        // 行ごとに明るさの異なる横縞 (水平方向のブラーでは変化しない)
+        let mut stripes = vec![0u8; 8 * 8 * 4];
+        for y in 0..8 {
+            let value = if y % 2 == 0 { 0 } else { 255 };
+            for x in 0..8 {
+                let idx = (y * 8 + x) * 4;
+                stripes[idx..idx + 4].copy_from_slice(&[value, value, value, 255]);
+            }
+        }
+        let mut data = stripes.clone();
+        processor.gaussian_blur_xy(&mut data, 2.0, 0.0);
+        assert_eq!(data, stripes);
+        
+        // 一般の画像では水平方向のみのブラーと一致する
+        let original_data = create_test_image_data(8, 8);
+        let mut xy = original_data.clone();
+        let mut horizontal = original_data.clone();
+        processor.gaussian_blur_xy(&mut xy, 2.0, 0.0);
+        processor.blur_horizontal(&mut horizontal, 8, 8, 2.0);
+        assert_eq!(xy, horizontal);
+        assert!(xy != original_data);
+    }
+
+    #[test]
+    fn test_pixel_sort_rows() {
+        let mut processor = ImageProcessor::new(4, 2);
+        let mut data = vec![
+            // 1行目: すべて閾値以下
+            40, 40, 40, 255, 10, 10, 10, 255, 30, 30, 30, 255, 20, 20, 20, 255,
+            // 2行目: すべて閾値を超える
+            250, 250, 250, 255, 120, 120, 120, 255, 200, 200, 200, 255, 150, 150, 150, 255,
+        ];
+        let first_row = data[0..16].to_vec();
+        
+        processor.pixel_sort(&mut data, 100, true);
+        
+        // 閾値以下の行は変化なし
+        assert_eq!(data[0..16], first_row[..]);
+        // 閾値を超える行は輝度の昇順に並ぶ
+        let second_row: Vec<u8> = data[16..32].chunks_exact(4).map(|p| p[0]).collect();
+        assert_eq!(second_row, vec![120, 150, 200, 250]);
+    }
+
+    #[test]
+    fn test_apply_n_negative_twice_is_identity() {
+        let mut processor = ImageProcessor::new(4, 4);
+        let original_data = create_test_image_data(4, 4);
+        
+        let mut data = original_data.clone();
+        processor.apply_n(&mut data, Filter::Negative, 2);
+        assert_eq!(data, original_data);
+        
+        // 1回だけなら通常のapplyと同じ
+        let mut once = original_data.clone();
+        let mut expected = original_data.clone();
+        processor.apply_n(&mut once, Filter::Negative, 1);
+        processor.apply(&mut expected, Filter::Negative);
+        assert_eq!(once, expected);
+    }
+
+    #[test]
+    fn test_luma_percentile() {
+        let processor = ImageProcessor::new(5, 1);
+        // 輝度 10, 20, 30, 40, 50 のグレー画素
+        let mut data = Vec::new();
+        for value in [30u8, 10, 50, 20, 40] {
+            data.extend_from_slice(&[value, value, value, 255]);
+        }
+        
+        assert_eq!(processor.luma_percentile(&data, 50.0), 30);
+        assert_eq!(processor.luma_percentile(&data, 0.0), 10);
+        assert_eq!(processor.luma_percentile(&data, 100.0), 50);
+        assert_eq!(processor.luma_percentile(&[], 50.0), 0);
+    }
+
+    #[test]
+    fn test_adaptive_threshold_uneven_lighting() {
+        let mut processor = ImageProcessor::new(16, 4);
+        // 左から右へ明るくなる照明の上に、背景より暗い縦線を2本描いた画像
+        let mut data = vec![0u8; 16 * 4 * 4];
+        for y in 0..4 {
+            for x in 0..16 {
+                let background = 40 + x as u8 * 12;
+                let value = if x == 3 || x == 12 { background - 40 } else { background };
+                let idx = (y * 16 + x) * 4;
+                data[idx..idx + 4].copy_from_slice(&[value, value, value, 255]);
+            }
+        }
+        
+        processor.adaptive_threshold(&mut data, 5, 20);
+        
+        // 線は暗い側・明るい側のどちらでも黒、背景は白になる
+        // (単一の閾値128では暗い側の背景が黒に、明るい側の線が白になってしまう)
+        for x in 0..16 {
+            let expected = if x == 3 || x == 12 { 0 } else { 255 };
+            assert_eq!(data[(16 + x) * 4], expected, "x = {}", x);
+        }
+    }
+
+    #[test]
+    fn test_high_pass_flat_is_mid_gray() {
+        let mut processor = ImageProcessor::new(8, 8);
+        let mut data = [90, 150, 30, 255].repeat(64);
+        
+        processor.high_pass(&mut data, 2.0);
+        
+        for pixel in data.chunks_exact(4) {
+            assert_eq!(pixel, [128, 128, 128, 255]);
+        }
+    }
+
+    #[test]
+    fn test_frequency_separation_round_trip() {
+        let processor = ImageProcessor::new(8, 8);
+        let original_data = create_test_image_data(8, 8);
+        
+        let layers = processor.frequency_separation(&original_data, 2.0);
+        assert_eq!(layers.len(), original_data.len() * 2);
+        let (low, high) = layers.split_at(original_data.len());
+        
+        // 加工しないレイヤーを合成すると±1以内で元画像に戻る
+        let recombined = processor.try_recombine_frequency(low, high).unwrap();
+        for (value, original) in recombined.iter().zip(&original_data) {
+            assert!(value.abs_diff(*original) <= 1);
+        }
+        
+        assert!(processor.try_recombine_frequency(low, &high[..8]).is_err());
+    }
+
+    #[test]
+    fn test_gaussian_blur_linear_brighter_midpoint() {
+        let mut processor = ImageProcessor::new(8, 8);
+        // 白黒のチェッカーボード
+        let mut checker = vec![0u8; 8 * 8 * 4];
+        for y in 0..8 {
+            for x in 0..8 {
+                let value = if (x + y) % 2 == 0 { 255 } else { 0 };
+                let idx = (y * 8 + x) * 4;
+                checker[idx..idx + 4].copy_from_slice(&[value, value, value, 255]);
+            }
+        }
+        
+        let mut naive = checker.clone();
+        let mut linear = checker.clone();
+        processor.gaussian_blur(&mut naive, 1.0);
+        processor.gaussian_blur_linear(&mut linear, 1.0);
+        
+        // リニア空間でぼかした方が中間調が明るい
+        let center = (4 * 8 + 4) * 4;
+        assert!(linear[center] > naive[center]);
+        assert_eq!(linear[center + 3], 255);
+    }
+
+    #[test]
+    fn test_vignette_rectangular_keeps_larger_center() {
+        let mut processor = ImageProcessor::new(16, 16);
+        let original_data = [200, 200, 200, 255].repeat(16 * 16);
+        
+        let count_untouched = |data: &[u8]| {
+            data.chunks_exact(4).filter(|p| p[0] == 200).count()
+        };
+        
+        let mut elliptical = original_data.clone();
+        let mut rectangular = original_data.clone();
+        processor.vignette(&mut elliptical, 0.5, 0.8, VignetteShape::Elliptical);
+        processor.vignette(&mut rectangular, 0.5, 0.8, VignetteShape::Rectangular);
+        
+        assert!(count_untouched(&rectangular) > count_untouched(&elliptical));
+        // 中心は変化なし、角は暗くなる
+        let center = (8 * 16 + 8) * 4;
+        assert_eq!(elliptical[center], 200);
+        assert!(elliptical[0] < 200);
+        assert!(rectangular[0] < 200);
+    }
+
+    #[test]
+    fn test_colored_sketch_flat_bright_is_paper() {
+        let mut processor = ImageProcessor::new(8, 8);
+        let paper = [240, 220, 180];
+        let ink = [40, 30, 90];
+        let mut data = [230, 230, 230, 255].repeat(64);
+        
+        processor.try_colored_sketch(&mut data, &paper, &ink).unwrap();
+        
+        // 平坦な明るい画像は紙の色になる
+        for pixel in data.chunks_exact(4) {
+            assert_eq!(pixel[..3], paper);
+            assert_eq!(pixel[3], 255);
+        }
+        
+        assert!(processor.try_colored_sketch(&mut data, &[0, 0], &ink).is_err());
+    }
+
+    #[test]
+    fn test_srgb_linear_round_trip() {
+        for c in 0..=255u8 {
+            let linear = srgb_to_linear(c);
+            assert!((0.0..=1.0).contains(&linear));
+            assert!(linear_to_srgb(linear).abs_diff(c) <= 1, "c = {}", c);
+        }
+        
+        // 端点と中間値 (リニア0.5はsRGBで約188)
+        assert_eq!(srgb_to_linear(0), 0.0);
+        assert!((srgb_to_linear(255) - 1.0).abs() < 1e-6);
+        assert_eq!(linear_to_srgb(0.5), 188);
+        assert_eq!(linear_to_srgb(2.0), 255);
+    }
+
+    #[test]
+    fn test_tile_fill() {
+        let processor = ImageProcessor::new(5, 3);
+        
+        // 1x1の赤で全体が赤になる
+        let red = processor.try_tile_fill(&[255, 0, 0, 255], 1, 1).unwrap();
+        assert_eq!(red, [255, 0, 0, 255].repeat(5 * 3));
+        
+        // 2x1のパターンは横方向に繰り返される
+        let pattern = [0, 0, 0, 255, 255, 255, 255, 255];
+        let tiled = processor.try_tile_fill(&pattern, 2, 1).unwrap();
+        assert_eq!(tiled[16..20], [0, 0, 0, 255]);
+        assert_eq!(tiled[(5 + 1) * 4..(5 + 1) * 4 + 4], [255, 255, 255, 255]);
+        
+        assert!(processor.try_tile_fill(&pattern, 1, 1).is_err());
+        assert!(processor.try_tile_fill(&[], 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_edge_overlay_keeps_non_edges() {
+        let mut processor = ImageProcessor::new(8, 8);
+        // 左半分が青、右半分が黄色の縦エッジ
+        let mut data = vec![0u8; 8 * 8 * 4];
+        for y in 0..8 {
+            for x in 0..8 {
+                let idx = (y * 8 + x) * 4;
+                let pixel = if x < 4 { [20, 40, 160, 255] } else { [230, 220, 40, 255] };
+                data[idx..idx + 4].copy_from_slice(&pixel);
+            }
+        }
+        let original_data = data.clone();
+        
+        processor.try_edge_overlay(&mut data, &[255, 0, 255], 100).unwrap();
+        
+        // エッジ上は指定色、それ以外は元の色のまま
+        let edge_idx = (4 * 8 + 4) * 4;
+        assert_eq!(data[edge_idx..edge_idx + 4], [255, 0, 255, 255]);
+        for x in [0, 1, 6, 7] {
+            let idx = (4 * 8 + x) * 4;
+            assert_eq!(data[idx..idx + 4], original_data[idx..idx + 4]);
+        }
+        
+        assert!(processor.try_edge_overlay(&mut data, &[255, 0], 100).is_err());
+    }
+
+    #[test]
+    fn test_gaussian_blur_reflect_no_dark_rim() {
+        let mut processor = ImageProcessor::new(12, 12);
+        // 外周3画素が白、内側が黒の画像
+        let mut data = vec![0u8; 12 * 12 * 4];
+        for y in 0..12 {
+            for x in 0..12 {
+                let value = if x < 3 || y < 3 || x >= 9 || y >= 9 { 255 } else { 0 };
+                let idx = (y * 12 + x) * 4;
+                data[idx..idx + 4].copy_from_slice(&[value, value, value, 255]);
+            }
+        }
+        
+        processor.gaussian_blur_reflect(&mut data, 2.0);
+        
+        // 最外周は白のまま、内側の隣の画素より暗くならない
+        for i in 0..12 {
+            for (rim, inner) in [((0, i), (1, i)), ((11, i), (10, i)), ((i, 0), (i, 1)), ((i, 11), (i, 10))] {
+                let rim_value = data[(rim.1 * 12 + rim.0) * 4];
+                let inner_value = data[(inner.1 * 12 + inner.0) * 4];
+                assert_eq!(rim_value, 255);
+                assert!(rim_value >= inner_value);
+            }
+        }
+        
+        // 一様な画像は変化しない
+        let mut uniform = [200, 180, 160, 255].repeat(12 * 12);
+        processor.gaussian_blur_reflect(&mut uniform, 3.0);
+        assert_eq!(uniform, [200, 180, 160, 255].repeat(12 * 12));
+        
+        assert_eq!(reflect_index(-1, 5), 1);
+        assert_eq!(reflect_index(5, 5), 3);
+        assert_eq!(reflect_index(-3, 1), 0);
+    }
+
+    #[test]
+    fn test_hdr_anime_outlined_draws_outline_color() {
+        let mut processor = ImageProcessor::new(12, 12);
+        // 左半分が暗く右半分が明るい縦エッジ
+        let mut data = vec![0u8; 12 * 12 * 4];
+        for y in 0..12 {
+            for x in 0..12 {
+                let idx = (y * 12 + x) * 4;
+                let value = if x < 6 { 40 } else { 220 };
+                data[idx..idx + 4].copy_from_slice(&[value, value, value, 255]);
+            }
+        }
+        let outline = [10, 20, 30];
+        
+        let mut thin = data.clone();
+        processor.try_hdr_anime_outlined(&mut thin, &outline, 1, 100).unwrap();
+        let mut thick = data.clone();
+        processor.try_hdr_anime_outlined(&mut thick, &outline, 4, 100).unwrap();
+        
+        let count_outline = |image: &[u8]| {
+            (0..12).filter(|&x| image[(6 * 12 + x) * 4..(6 * 12 + x) * 4 + 3] == outline).count()
+        };
+        
+        // 強いエッジは指定色になり、太さに応じて線幅が広がる
+        assert_eq!(thin[(6 * 12 + 6) * 4..(6 * 12 + 6) * 4 + 3], outline);
+        assert!(count_outline(&thick) > count_outline(&thin));
+        // 平坦な領域は輪郭線にならない
+        assert!(thin[(6 * 12) * 4..(6 * 12) * 4 + 3] != outline);
+        
+        // 太さ0ではhdr_animeと同じ結果になる
+        let mut plain = data.clone();
+        processor.try_hdr_anime_outlined(&mut plain, &outline, 0, 100).unwrap();
+        let mut hdr_anime = data.clone();
+        processor.hdr_anime(&mut hdr_anime);
+        assert_eq!(plain, hdr_anime);
+        assert_eq!(count_outline(&plain), 0);
+        
+        assert!(processor.try_hdr_anime_outlined(&mut plain, &[0, 0], 1, 100).is_err());
+    }
+
+    #[test]
+    fn test_apply_ops_matches_manual_calls() {
+        let mut processor = ImageProcessor::new(8, 8);
+        let original_data = create_test_image_data(8, 8);
+        
+        let mut batched = original_data.clone();
+        processor.try_apply_ops(
+            &mut batched,
+            r#"[{ "filter": "gaussian_blur", "params": { "radius": 2.0 } }, { "filter": "sepia_tone" }]"#,
+        ).unwrap();
+        
+        let mut manual = original_data.clone();
+        processor.gaussian_blur(&mut manual, 2.0);
+        processor.sepia_tone(&mut manual);
+        
+        assert_eq!(batched, manual);
+    }
+
+    #[test]
+    fn test_apply_ops_errors() {
+        let mut processor = ImageProcessor::new(4, 4);
+        let mut data = create_test_image_data(4, 4);
+        
+        // 未知のフィルタ名はフィルタ名を含むエラー
+        let err = processor.try_apply_ops(&mut data, r#"[{ "filter": "sparkle" }]"#).unwrap_err();
+        assert!(err.contains("sparkle"));
+        
+        assert!(processor.try_apply_ops(&mut data, "not json").is_err());
+        assert!(processor.try_apply_ops(&mut data, r#"{ "filter": "negative" }"#).is_err());
+        assert!(processor.try_apply_ops(&mut data, r#"[{ "filter": "gaussian_blur", "params": { "radius": "big" } }]"#).is_err());
+        assert!(processor.try_apply_ops(&mut data, r#"[{ "filter": "posterize", "params": { "levels": 1 } }]"#).is_err());
+    }
+
+    #[test]
+    fn test_apply_ops_invalid_op_leaves_data_unchanged() {
+        let mut processor = ImageProcessor::new(4, 4);
+        let original_data = create_test_image_data(4, 4);
+        
+        // 2番目の操作が不正な場合は1番目の操作も適用しない
+        for preset in [
+            r#"[{ "filter": "negative" }, { "filter": "sparkle" }]"#,
+            r#"[{ "filter": "negative" }, { "filter": "gaussian_blur", "params": { "radius": "big" } }]"#,
+            r#"[{ "filter": "negative" }, { "filter": "posterize", "params": { "levels": 1 } }]"#,
+        ] {
+            let mut data = original_data.clone();
+            let err = processor.try_apply_ops(&mut data, preset).unwrap_err();
+            assert!(err.contains("op 1"));
+            assert_eq!(data, original_data);
+        }
+    }
+
+    #[test]
+    fn test_resize_bilinear_linear_is_brighter() {
+        let processor = ImageProcessor::new(8, 8);
+        // 白黒のチェッカーボード
+        let mut checker = vec![0u8; 8 * 8 * 4];
+        for y in 0..8 {
+            for x in 0..8 {
+                let value = if (x + y) % 2 == 0 { 255 } else { 0 };
+                let idx = (y * 8 + x) * 4;
+                checker[idx..idx + 4].copy_from_slice(&[value, value, value, 255]);
+            }
+        }
+        
+        let srgb = processor.resize_bilinear(&checker, 4, 4, false);
+        let linear = processor.resize_bilinear(&checker, 4, 4, true);
+        
+        assert_eq!(srgb.len(), 4 * 4 * 4);
+        assert_eq!(linear.len(), 4 * 4 * 4);
+        // sRGBでは単純平均の約128、リニア空間では約188になる
+        assert_eq!(srgb[0], 128);
+        assert!(linear[0] > srgb[0]);
+        assert_eq!(linear[0], 188);
+        assert_eq!(linear[3], 255);
+    }
+
+    #[test]
+    fn test_average_color_region() {
+        let processor = ImageProcessor::new(6, 4);
+        let mut data = create_test_image_data(6, 4);
+        // (2, 1) から 3x2 の領域を一様な色で塗る
+        for y in 1..3 {
+            for x in 2..5 {
+                let idx = (y * 6 + x) * 4;
+                data[idx..idx + 4].copy_from_slice(&[90, 120, 200, 255]);
+            }
+        }
+        
+        assert_eq!(processor.try_average_color(&data, 2, 1, 3, 2).unwrap(), vec![90, 120, 200, 255]);
+        
+        // 範囲外・空の領域はエラー
+        assert!(processor.try_average_color(&data, 4, 0, 3, 1).is_err());
+        assert!(processor.try_average_color(&data, 0, 3, 1, 2).is_err());
+        assert!(processor.try_average_color(&data, 0, 0, 0, 1).is_err());
+    }
+
+    #[test]
+    fn test_auto_white_balance_neutralizes_cast() {
+        let mut processor = ImageProcessor::new(4, 4);
+        // 全体が青みがかった画像
+        let mut data = Vec::new();
+        for i in 0..16u8 {
+            data.extend_from_slice(&[60 + i * 4, 70 + i * 4, 150 + i * 4, 255]);
+        }
+        
+        let channel_means = |image: &[u8]| {
+            let mut sums = [0.0f32; 3];
+            for pixel in image.chunks_exact(4) {
                 for c in 0..3 {
-                    let original = temp_data[idx + c] as f32;
-                    let enhanced = original * enhancement;
-                    data[idx + c] = enhanced.min(255.0) as u8;
+                    sums[c] += pixel[c] as f32;
                 }
             }
+            sums.map(|s| s / 16.0)
+        };
+        let spread = |m: [f32; 3]| m.iter().cloned().fold(f32::MIN, f32::max) - m.iter().cloned().fold(f32::MAX, f32::min);
+        
+        let before = spread(channel_means(&data));
+        processor.auto_white_balance(&mut data);
+        let after = spread(channel_means(&data));
+        
+        assert!(after < before);
+        assert!(after < 2.0);
+        assert_eq!(data[3], 255);
+    }
+
+    #[test]
+    fn test_ghost_trail_leaves_faded_trail() {
+        let mut processor = ImageProcessor::new(4, 1);
+        let black = [0, 0, 0, 255].repeat(4);
+        
+        // 明るい画素が x=0 から x=1 へ移動する
+        let mut first = black.clone();
+        first[0..3].fill(200);
+        processor.ghost_trail(&mut first, 0.5);
+        assert_eq!(first[0], 200);
+        
+        let mut second = black.clone();
+        second[4..7].fill(200);
+        processor.ghost_trail(&mut second, 0.5);
+        
+        // 元の位置には減衰した残像が残る
+        assert_eq!(second[0], 100);
+        assert_eq!(second[4], 200);
+        assert_eq!(second[8], 0);
+        
+        // 次のフレームでは残像がさらに薄くなる
+        let mut third = black.clone();
+        processor.ghost_trail(&mut third, 0.5);
+        assert_eq!(third[0], 50);
+        assert_eq!(third[4], 100);
+    }
+
+    #[test]
+    fn test_circular_mask() {
+        let mut processor = ImageProcessor::new(16, 16);
+        let mut data = create_test_image_data(16, 16);
+        let original_data = data.clone();
+        
+        processor.circular_mask(&mut data, 2.0);
+        
+        // 角は透明、中心は不透明のまま
+        for (x, y) in [(0, 0), (15, 0), (0, 15), (15, 15)] {
+            assert_eq!(data[(y * 16 + x) * 4 + 3], 0);
+        }
+        let center = (8 * 16 + 8) * 4;
+        assert_eq!(data[center + 3], 255);
+        // RGBは変更しない
+        assert_eq!(data[center..center + 3], original_data[center..center + 3]);
+        // 縁は中間の透明度になる
+        let rim_alpha = data[(8 * 16) * 4 + 3];
+        assert!(rim_alpha > 0 && rim_alpha < 255);
+    }
+
+    #[test]
+    fn test_tritone_bands() {
+        let mut processor = ImageProcessor::new(3, 1);
+        let shadow = [20, 0, 60];
+        let mid = [200, 60, 90];
+        let highlight = [255, 230, 150];
+        // 暗い・中間・明るいグレー
+        let mut data = vec![30, 30, 30, 255, 128, 128, 128, 255, 240, 240, 240, 128];
+        
+        processor.try_tritone(&mut data, &shadow, &mid, &highlight, 80, 180).unwrap();
+        
+        assert_eq!(data[0..4], [20, 0, 60, 255]);
+        assert_eq!(data[4..8], [200, 60, 90, 255]);
+        assert_eq!(data[8..12], [255, 230, 150, 128]);
+        
+        assert!(processor.try_tritone(&mut data, &shadow, &mid, &highlight, 200, 100).is_err());
+        assert!(processor.try_tritone(&mut data, &shadow, &[0], &highlight, 80, 180).is_err());
+    }
+
+    #[test]
+    fn test_sharpness_score_drops_after_blur() {
+        let mut processor = ImageProcessor::new(16, 16);
+        // 白黒のチェッカーボード (2x2のマス)
+        let mut sharp = vec![0u8; 16 * 16 * 4];
+        for y in 0..16 {
+            for x in 0..16 {
+                let value = if (x / 2 + y / 2) % 2 == 0 { 255 } else { 0 };
+                let idx = (y * 16 + x) * 4;
+                sharp[idx..idx + 4].copy_from_slice(&[value, value, value, 255]);
+            }
         }
+        let mut blurred = sharp.clone();
+        processor.gaussian_blur(&mut blurred, 2.0);
+        
+        assert!(processor.sharpness_score(&blurred) < processor.sharpness_score(&sharp));
+        
+        // 平坦な画像は0
+        let flat = [128, 128, 128, 255].repeat(16 * 16);
+        assert_eq!(processor.sharpness_score(&flat), 0.0);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_pixelate_most_frequent_keeps_majority() {
+        let mut processor = ImageProcessor::new(2, 2);
+        // 赤3画素と青1画素のブロック
+        let original_data = vec![
+            255, 0, 0, 255, 255, 0, 0, 255,
+            255, 0, 0, 255, 0, 0, 255, 255,
+        ];
+        
+        let mut frequent = original_data.clone();
+        processor.pixelate(&mut frequent, 2, PixelateMode::MostFrequent);
+        assert_eq!(frequent, [255, 0, 0, 255].repeat(4));
+        
+        // 平均では色が混ざる
+        let mut average = original_data.clone();
+        processor.pixelate(&mut average, 2, PixelateMode::Average);
+        assert_eq!(average, [191, 0, 64, 255].repeat(4));
+    }
 
-    /// テスト用の小さな画像データを作成
-    fn create_test_image_data(width: u32, height: u32) -> Vec<u8> {
+    #[test]
+    fn test_portrait_blur_keeps_subject_sharp() {
+        let mut processor = ImageProcessor::new(8, 8);
+        let original_data = create_test_image_data(8, 8);
+        
+        // 中央の4x4を被写体とする
+        let mask: Vec<u8> = (0..64)
+            .map(|i| if (2..6).contains(&(i % 8)) && (2..6).contains(&(i / 8)) { 255 } else { 0 })
+            .collect();
+        let mut data = original_data.clone();
+        processor.try_portrait_blur(&mut data, &mask, 2.0).unwrap();
+        
+        let mut blurred = original_data.clone();
+        processor.gaussian_blur(&mut blurred, 2.0);
+        for (i, &m) in mask.iter().enumerate() {
+            let pixel = &data[i * 4..i * 4 + 4];
+            if m == 255 {
+                assert_eq!(pixel, &original_data[i * 4..i * 4 + 4]);
+            } else {
+                assert_eq!(pixel, &blurred[i * 4..i * 4 + 4]);
+            }
+        }
+        
+        assert!(processor.try_portrait_blur(&mut data, &mask[..10], 2.0).is_err());
+        assert!(processor.try_portrait_blur(&mut data[..100], &mask, 2.0).is_err());
+    }
+
+    #[test]
+    fn test_describe_filters_catalog() {
+        let catalog: serde_json::Value = serde_json::from_str(&describe_filters()).unwrap();
+        let filters = catalog.as_array().unwrap();
+        
+        let blur = filters.iter().find(|f| f["name"] == "gaussian_blur").unwrap();
+        let radius = &blur["params"][0];
+        assert_eq!(radius["name"], "radius");
+        assert_eq!(radius["default"], 3.0);
+        assert!(radius["min"].as_f64().unwrap() <= radius["max"].as_f64().unwrap());
+        
+        let levels = filters.iter().find(|f| f["name"] == "posterize").unwrap();
+        assert_eq!(levels["params"][0]["type"], "integer");
+        
+        // 一覧とapply_opsで指定できるフィルタは同じ定義から作られる
+        let described: Vec<&str> = filters.iter().map(|f| f["name"].as_str().unwrap()).collect();
+        let catalog_names: Vec<&str> = FILTER_CATALOG.iter().map(|f| f.name).collect();
+        assert_eq!(described, catalog_names);
+        let mut unique = catalog_names.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), catalog_names.len());
+        
+        // 一覧のすべてのフィルタはデフォルト値のままapply_opsで適用でき、
+        // デフォルト値は範囲内にある
+        let mut processor = ImageProcessor::new(4, 4);
+        let mut data = create_test_image_data(4, 4);
+        for filter in filters {
+            let op = format!(r#"[{{ "filter": {} }}]"#, filter["name"]);
+            processor.try_apply_ops(&mut data, &op).unwrap();
+            for param in filter["params"].as_array().unwrap() {
+                let default = param["default"].as_f64().unwrap();
+                assert!(param["min"].as_f64().unwrap() <= default && default <= param["max"].as_f64().unwrap());
+            }
+        }
+        
+        // 一覧にないフィルタはapply_opsでも指定できない
+        assert!(processor.try_apply_ops(&mut data, r#"[{ "filter": "symmetry" }]"#).is_err());
+        assert!(processor.try_apply_ops(&mut data, r#"[{ "filter": "dither_ordered", "params": { "matrix_size": 3 } }]"#).is_err());
+    }
+
+    #[test]
+    fn test_luma_weights_bt709() {
+        let mut processor = ImageProcessor::new(1, 1);
+        let green = [0, 255, 0, 255];
+        
+        // BT.601では 255 * 0.587 ≒ 149
+        assert_eq!(processor.gray_buffer(&green), vec![149]);
+        
+        // BT.709では 255 * 0.7152 ≒ 182
+        processor.set_luma_weights(LumaWeights::Bt709);
+        assert_eq!(processor.gray_buffer(&green), vec![182]);
+        assert_eq!(processor.luma_percentile(&green, 50.0), 182);
+        
+        // 派生したプロセッサにも引き継がれる
+        assert_eq!(processor.sub_processor(1, 1).gray_buffer(&green), vec![182]);
+    }
+
+    #[test]
+    fn test_auto_enhance_widens_range_and_neutralizes() {
+        let mut processor = ImageProcessor::new(4, 4);
+        // 低コントラストで青みがかった画像
         let mut data = Vec::new();
-        for y in 0..height {
-            for x in 0..width {
-                // グラデーション画像を作成
-                let r = (x * 255 / width) as u8;
-                let g = (y * 255 / height) as u8;
-                let b = 128;
-                let a = 255;
-                data.extend_from_slice(&[r, g, b, a]);
+        for i in 0..16u8 {
+            data.extend_from_slice(&[90 + i * 2, 100 + i * 2, 140 + i * 2, 255]);
+        }
+        
+        let stats = |image: &[u8]| {
+            let mut sums = [0.0f32; 3];
+            let mut lumas = Vec::new();
+            for pixel in image.chunks_exact(4) {
+                for c in 0..3 {
+                    sums[c] += pixel[c] as f32;
+                }
+                lumas.push(pixel[0] as u32 + pixel[1] as u32 + pixel[2] as u32);
             }
+            let means = sums.map(|s| s / 16.0);
+            let spread = means.iter().cloned().fold(f32::MIN, f32::max) - means.iter().cloned().fold(f32::MAX, f32::min);
+            let range = lumas.iter().max().unwrap() - lumas.iter().min().unwrap();
+            (spread, range)
+        };
+        
+        let (spread_before, range_before) = stats(&data);
+        processor.auto_enhance(&mut data);
+        let (spread_after, range_after) = stats(&data);
+        
+        assert!(range_after > range_before);
+        assert!(spread_after < spread_before);
+        assert_eq!(data[3], 255);
+    }
+
+    #[test]
+    fn test_spotlight_brightens_center_darkens_far() {
+        let mut processor = ImageProcessor::new(16, 16);
+        let mut data = [100, 100, 100, 255].repeat(16 * 16);
+        
+        processor.spotlight(&mut data, 4.0, 4.0, 3.0, 4.0);
+        
+        let center = (4 * 16 + 4) * 4;
+        let far = (15 * 16 + 15) * 4;
+        assert!(data[center] > 100);
+        assert!(data[far] < 100);
+        assert_eq!(data[far + 3], 255);
+    }
+
+    #[test]
+    fn test_estimate_cost_scales_with_size() {
+        let small = ImageProcessor::new(320, 240);
+        let large = ImageProcessor::new(1280, 720);
+        
+        for filter in [Filter::HdrAnime, Filter::GaussianBlur, Filter::EdgeDetection, Filter::SepiaTone, Filter::Negative, Filter::Sharpen] {
+            assert!(large.estimate_cost(filter) > small.estimate_cost(filter));
         }
-        data
+        // ブラーは画素単位の反転より重い
+        assert!(small.estimate_cost(Filter::GaussianBlur) > small.estimate_cost(Filter::Negative));
     }
 
     #[test]
-    fn test_image_processor_creation() {
-        let processor = ImageProcessor { width: 100, height: 100 };
-        assert_eq!(processor.width, 100);
-        assert_eq!(processor.height, 100);
+    fn test_watercolor_smooths_flat_region() {
+        let mut processor = ImageProcessor::new(16, 16);
+        // 平坦な灰色に細かいノイズを加えた画像
+        let mut rng = XorShift32::new(7);
+        let mut data = vec![255u8; 16 * 16 * 4];
+        for pixel in data.chunks_exact_mut(4) {
+            let value = 118 + (rng.next_u32() % 21) as u8;
+            pixel[..3].fill(value);
+        }
+        let original_data = data.clone();
+        
+        processor.watercolor(&mut data, 2);
+        
+        assert_eq!(data.len(), original_data.len());
+        // 中央付近の画素のばらつきが小さくなっている
+        let spread = |buffer: &[u8]| {
+            let values: Vec<u8> = (4..12)
+                .flat_map(|y| (4..12).map(move |x| buffer[(y * 16 + x) * 4]))
+                .collect();
+            values.iter().max().unwrap() - values.iter().min().unwrap()
+        };
+        assert!(spread(&data) < spread(&original_data));
+        // Alpha値は保持
+        assert!(data.chunks_exact(4).all(|p| p[3] == 255));
     }
 
-    // WebAssembly固有の機能を使わない純粋なロジックテスト
-    
     #[test]
-    fn test_gaussian_blur_preserves_data_length() {
-        let mut processor = ImageProcessor { width: 10, height: 10 };
-        let mut data = create_test_image_data(10, 10);
-        let original_len = data.len();
+    fn test_gaussian_blur_premul_no_dark_fringe() {
+        let mut processor = ImageProcessor::new(16, 4);
+        // 左半分は透明な黒、右半分は不透明な赤
+        let mut data = vec![0u8; 16 * 4 * 4];
+        for y in 0..4 {
+            for x in 8..16 {
+                let idx = (y * 16 + x) * 4;
+                data[idx..idx + 4].copy_from_slice(&[255, 0, 0, 255]);
+            }
+        }
         
-        processor.gaussian_blur(&mut data, 2.0);
+        processor.gaussian_blur_premul(&mut data, 3.0);
         
-        assert_eq!(data.len(), original_len);
-        // Alpha値は変更されないことを確認
-        for i in (0..data.len()).step_by(4) {
-            assert_eq!(data[i + 3], 255);
+        // 境界付近の半透明画素も赤のまま (黒ずまない)
+        for pixel in data.chunks_exact(4).filter(|p| p[3] > 0) {
+            assert_eq!(pixel[..3], [255, 0, 0]);
         }
+        // Alphaはぼかされて中間値を持つ
+        assert!(data.chunks_exact(4).any(|p| p[3] > 0 && p[3] < 255));
     }
 
     #[test]
-    fn test_gaussian_blur_zero_radius() {
-        let mut processor = ImageProcessor { width: 5, height: 5 };
-        let mut data = create_test_image_data(5, 5);
+    fn test_draw_grid_rule_of_thirds() {
+        let mut processor = ImageProcessor::new(9, 6);
+        let mut data = create_test_image_data(9, 6);
         let original_data = data.clone();
         
-        processor.gaussian_blur(&mut data, 0.0);
+        processor.draw_grid(&mut data, 3, 3, 0, 255, 0);
         
-        // 半径0の場合、データは変更されない
-        assert_eq!(data, original_data);
+        for y in 0..6 {
+            for x in 0..9 {
+                let idx = (y * 9 + x) * 4;
+                // 水平線は y = 2, 4、垂直線は x = 3, 6
+                if y == 2 || y == 4 || x == 3 || x == 6 {
+                    assert_eq!(data[idx..idx + 3], [0, 255, 0]);
+                    assert_eq!(data[idx + 3], original_data[idx + 3]);
+                } else {
+                    assert_eq!(data[idx..idx + 4], original_data[idx..idx + 4]);
+                }
+            }
+        }
     }
 
     #[test]
-    fn test_sepia_tone_effect() {
-        let mut processor = ImageProcessor { width: 2, height: 2 };
-        let mut data = vec![
-            255, 0, 0, 255,    // 赤
-            0, 255, 0, 255,    // 緑
-            0, 0, 255, 255,    // 青
-            255, 255, 255, 255 // 白
-        ];
+    fn test_dehaze_restores_range() {
+        let mut processor = ImageProcessor::new(32, 4);
+        // 明るく持ち上げられた低コントラストの横グラデーション (180..=211)
+        let mut data = Vec::new();
+        for _ in 0..4 {
+            for x in 0..32u8 {
+                let value = 180 + x;
+                data.extend_from_slice(&[value, value, value - 10, 255]);
+            }
+        }
+        let original_data = data.clone();
+        let range = |buffer: &[u8]| {
+            let values: Vec<u8> = buffer.chunks_exact(4).map(|p| p[1]).collect();
+            values.iter().max().unwrap() - values.iter().min().unwrap()
+        };
         
-        processor.sepia_tone(&mut data);
+        // amount 0.0では変化しない
+        processor.dehaze(&mut data, 0.0);
+        assert_eq!(data, original_data);
         
-        // セピア効果が適用されていることを確認
-        // 赤い画素がセピア調になっているか
-        assert!(data[0] > 0);  // R成分
-        assert!(data[1] > 0);  // G成分
-        assert_eq!(data[3], 255); // Alpha値は保持
+        processor.dehaze(&mut data, 0.9);
+        
+        assert!(range(&data) > range(&original_data) * 2);
+        assert!(data.chunks_exact(4).all(|p| p[3] == 255));
     }
 
     #[test]
-    fn test_negative_effect() {
-        let mut processor = ImageProcessor { width: 2, height: 1 };
+    fn test_find_extremes() {
+        let processor = ImageProcessor::new(8, 6);
+        let mut data = vec![128u8; 8 * 6 * 4];
+        // (5, 3) だけ白、(1, 4) だけ黒
+        let white = (3 * 8 + 5) * 4;
+        data[white..white + 3].fill(255);
+        let black = (4 * 8 + 1) * 4;
+        data[black..black + 3].fill(0);
+        
+        assert_eq!(processor.find_extremes(&data), vec![5, 3, 1, 4]);
+        assert!(ImageProcessor::new(0, 0).find_extremes(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_shift_hue_range() {
+        let mut processor = ImageProcessor::new(4, 1);
+        // 赤, 青, グレー, 黄
         let mut data = vec![
-            255, 0, 128, 255,  // テスト色
-            100, 200, 50, 255  // テスト色
+            255, 0, 0, 255,
+            0, 0, 255, 200,
+            128, 128, 128, 255,
+            255, 255, 0, 255,
         ];
         let original_data = data.clone();
         
-        processor.negative(&mut data);
-        
-        // ネガ効果の確認
-        assert_eq!(data[0], 255 - original_data[0]); // R反転
-        assert_eq!(data[1], 255 - original_data[1]); // G反転
-        assert_eq!(data[2], 255 - original_data[2]); // B反転
-        assert_eq!(data[3], original_data[3]);       // Alpha保持
+        // 赤 (0度) ±30度を120度回転 → 緑
+        processor.shift_hue_range(&mut data, 0.0, 30.0, 120.0);
         
-        assert_eq!(data[4], 255 - original_data[4]); // R反転
-        assert_eq!(data[5], 255 - original_data[5]); // G反転
-        assert_eq!(data[6], 255 - original_data[6]); // B反転
-        assert_eq!(data[7], original_data[7]);       // Alpha保持
+        assert_eq!(data[0..4], [0, 255, 0, 255]);
+        // 範囲外の色と無彩色は変化しない
+        assert_eq!(data[4..], original_data[4..]);
     }
 
     #[test]
-    fn test_get_gray_value() {
-        let processor = ImageProcessor { width: 3, height: 1 };
-        let data = vec![
-            255, 0, 0, 255,    // 赤
-            0, 255, 0, 255,    // 緑
-            0, 0, 255, 255,    // 青
-        ];
+    fn test_hsv_round_trip() {
+        for rgb in [[255u8, 0, 0], [12, 200, 99], [250, 128, 3], [40, 40, 41], [0, 0, 0], [255, 255, 255]] {
+            let (h, s, v) = rgb_to_hsv(rgb[0], rgb[1], rgb[2]);
+            assert_eq!(hsv_to_rgb(h, s, v), rgb);
+        }
+    }
+
+    #[test]
+    fn test_blur_with_kernel_matches_gaussian_sigma() {
+        let mut processor = ImageProcessor::new(16, 16);
+        let original_data = create_test_image_data(16, 16);
         
-        let gray_red = processor.get_gray_value(&data, 0, 0, 3);
-        let gray_green = processor.get_gray_value(&data, 1, 0, 3);
-        let gray_blue = processor.get_gray_value(&data, 2, 0, 3);
+        // σ = 1.0 のガウシアンカーネル (±3σ)
+        let kernel = [0.00443, 0.05400, 0.24203, 0.39894, 0.24203, 0.05400, 0.00443];
+        let mut custom = original_data.clone();
+        processor.try_blur_with_kernel(&mut custom, &kernel).unwrap();
         
-        // グレースケール変換の妥当性確認
-        assert!(gray_red > 0);
-        assert!(gray_green > gray_red);  // 緑は赤より明るい
-        assert!(gray_blue > 0);
+        let mut reference = original_data.clone();
+        processor.gaussian_blur_sigma(&mut reference, 1.0);
+        
+        for (a, b) in custom.iter().zip(reference.iter()) {
+            assert!(a.abs_diff(*b) <= 1);
+        }
     }
 
     #[test]
-    fn test_data_integrity_after_multiple_effects() {
-        let mut processor = ImageProcessor { width: 10, height: 10 };
-        let mut data = create_test_image_data(10, 10);
-        let original_len = data.len();
+    fn test_blur_with_kernel_validation() {
+        let mut processor = ImageProcessor::new(2, 2);
+        let mut data = create_test_image_data(2, 2);
         
-        // 複数のエフェクトを順次適用
-        processor.gaussian_blur(&mut data, 1.0);
-        processor.sepia_tone(&mut data);
+        assert!(processor.try_blur_with_kernel(&mut data, &[0.5, 0.5]).is_err());
+        assert!(processor.try_blur_with_kernel(&mut data, &[1.0, 1.0, 1.0]).is_err());
+        assert!(processor.try_blur_with_kernel(&mut data, &[0.25, 0.5, 0.25]).is_ok());
+    }
+
+    #[test]
+    fn test_blend_with_mask() {
+        let processor = ImageProcessor::new(4, 2);
+        let a = create_test_image_data(4, 2);
+        let b: Vec<u8> = a.iter().map(|v| 255 - v).collect();
         
-        // データ長とAlpha値の整合性確認
-        assert_eq!(data.len(), original_len);
-        for i in (0..data.len()).step_by(4) {
-            assert_eq!(data[i + 3], 255);
+        assert_eq!(processor.try_blend_with_mask(&a, &b, &[0; 8]).unwrap(), a);
+        assert_eq!(processor.try_blend_with_mask(&a, &b, &[255; 8]).unwrap(), b);
+        
+        // 中間値では両者の間の値になる
+        let half = processor.try_blend_with_mask(&a, &b, &[128; 8]).unwrap();
+        for ((h, x), y) in half.iter().zip(a.iter()).zip(b.iter()) {
+            assert!((*x.min(y)..=*x.max(y)).contains(h));
         }
+        
+        // 長さの検証
+        assert!(processor.try_blend_with_mask(&a, &b[..4], &[0; 8]).is_err());
+        assert!(processor.try_blend_with_mask(&a, &b, &[0; 3]).is_err());
+        assert!(processor.try_blend_with_mask(&a[..4], &b[..4], &[0]).is_err());
     }
 
     #[test]
-    fn test_blur_horizontal_basic() {
-        let processor = ImageProcessor { width: 3, height: 1 };
-        let mut data = vec![
-            255, 0, 0, 255,    // 赤
-            0, 255, 0, 255,    // 緑  
-            0, 0, 255, 255,    // 青
-        ];
+    fn test_posterize_hsv_two_value_levels() {
+        let mut processor = ImageProcessor::new(16, 16);
+        // 暗い色から明るい色までのオレンジ系のグラデーション
+        let mut data = Vec::new();
+        for value in 0..=255u8 {
+            data.extend_from_slice(&[value, value / 2, value / 4, 255]);
+        }
         
-        processor.blur_horizontal(&mut data, 3, 1, 1.0);
+        processor.posterize_hsv(&mut data, 0, 0, 2);
         
-        // ブラー後もAlpha値は保持
-        assert_eq!(data[3], 255);
-        assert_eq!(data[7], 255);
-        assert_eq!(data[11], 255);
+        // 明度 (RGBの最大値) は0か255の2段階のみ
+        let mut values: Vec<u8> = data.chunks_exact(4).map(|p| p[0].max(p[1]).max(p[2])).collect();
+        values.sort_unstable();
+        values.dedup();
+        assert_eq!(values, vec![0, 255]);
+    }
+
+    #[test]
+    fn test_posterize_hsv_all_below_two_is_identity() {
+        let mut processor = ImageProcessor::new(8, 8);
+        let mut data = create_test_image_data(8, 8);
+        let original_data = data.clone();
+        
+        processor.posterize_hsv(&mut data, 1, 0, 1);
+        
+        assert_eq!(data, original_data);
     }
 }
\ No newline at end of file